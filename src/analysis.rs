@@ -0,0 +1,112 @@
+//! Analyses that summarize a whole `MergedSeries`, as opposed to the per time step analysis done
+//! when creating `AnalyzedData`.
+
+use crate::{timeseries::MergedSeries, types::AnalyzedData};
+use chrono::Timelike;
+use metfor::{CelsiusDiff, Meters, Quantity};
+use std::collections::HashSet;
+
+/// The minimum number of days with data at an hour to calculate the mean for that hour.
+const MIN_DAYS: usize = 3;
+
+/// The "typical day" for a series, the mean of the values at each UTC hour across all the days.
+#[derive(Clone, Debug, Default)]
+pub struct DiurnalCycle {
+    /// The mean values for each hour of the day, indexed by the UTC hour.
+    ///
+    /// The `valid_time` of each value is the first valid time at that hour in the series, and the
+    /// `lead_time` is the mean lead time. If there were fewer than 3 days with data at an hour, the
+    /// value is `None`.
+    pub hours: [Option<AnalyzedData>; 24],
+}
+
+impl DiurnalCycle {
+    /// Get the mean values for a UTC hour, `None` if there was not enough data or the hour is not
+    /// less than 24.
+    pub fn get(&self, hour: u32) -> Option<&AnalyzedData> {
+        self.hours.get(hour as usize).and_then(|anal| anal.as_ref())
+    }
+}
+
+/// Calculate the mean value of each field at each UTC hour of the day across all the days in the
+/// series.
+///
+/// NaN values are skipped when calculating the means, so a field is only NaN if it was missing
+/// on every day. The Haines Index is averaged and rounded, skipping the zeros that mark a missing
+/// value, and the wind direction is the direction of the mean unit vector.
+pub fn extract_diurnal_cycle(series: &MergedSeries<AnalyzedData>) -> DiurnalCycle {
+    let mut by_hour: Vec<Vec<&AnalyzedData>> = vec![vec![]; 24];
+    for anal in series.data.iter() {
+        by_hour[anal.valid_time.hour() as usize].push(anal);
+    }
+
+    let mut cycle = DiurnalCycle::default();
+    for (hour, anals) in by_hour.into_iter().enumerate() {
+        let num_days = anals
+            .iter()
+            .map(|anal| anal.valid_time.date())
+            .collect::<HashSet<_>>()
+            .len();
+
+        if num_days >= MIN_DAYS {
+            cycle.hours[hour] = Some(mean_of(&anals));
+        }
+    }
+
+    cycle
+}
+
+/// Calculate the mean of each field, `anals` must not be empty.
+fn mean_of(anals: &[&AnalyzedData]) -> AnalyzedData {
+    let mean = |field_fn: &dyn Fn(&AnalyzedData) -> f64| {
+        let (sum, count) = anals
+            .iter()
+            .copied()
+            .map(field_fn)
+            .filter(|val| !val.is_nan())
+            .fold((0.0, 0), |(sum, count), val| (sum + val, count + 1));
+
+        if count == 0 {
+            std::f64::NAN
+        } else {
+            sum / f64::from(count)
+        }
+    };
+
+    let haines_index = mean(&|anal| {
+        if anal.haines_index == 0 {
+            std::f64::NAN
+        } else {
+            f64::from(anal.haines_index)
+        }
+    });
+    let haines_index = if haines_index.is_nan() {
+        0
+    } else {
+        haines_index.round() as u8
+    };
+
+    // Wind directions wrap around, so average them as unit vectors, e.g. 350 and 10 average to 0.
+    let mean_dir = |dir_fn: &dyn Fn(&AnalyzedData) -> f64| {
+        let sin_mean = mean(&|anal| dir_fn(anal).to_radians().sin());
+        let cos_mean = mean(&|anal| dir_fn(anal).to_radians().cos());
+        sin_mean.atan2(cos_mean).to_degrees().rem_euclid(360.0)
+    };
+
+    AnalyzedData {
+        valid_time: anals[0].valid_time,
+        lead_time: mean(&|anal| f64::from(anal.lead_time)).round() as i32,
+        hdw: mean(&|anal| anal.hdw),
+        ffwi: mean(&|anal| anal.ffwi),
+        blow_up_dt: CelsiusDiff(mean(&|anal| anal.blow_up_dt.unpack())),
+        blow_up_height: Meters(mean(&|anal| anal.blow_up_height.unpack())),
+        haines_index,
+        mixing_height: Meters(mean(&|anal| anal.mixing_height.unpack())),
+        surface_rh: mean(&|anal| anal.surface_rh),
+        surface_temperature_c: mean(&|anal| anal.surface_temperature_c),
+        surface_wind_speed_ms: mean(&|anal| anal.surface_wind_speed_ms),
+        surface_wind_direction_deg: mean_dir(&|anal| anal.surface_wind_direction_deg),
+        transport_wind_speed_ms: mean(&|anal| anal.transport_wind_speed_ms),
+        transport_wind_dir_deg: mean_dir(&|anal| anal.transport_wind_dir_deg),
+    }
+}
@@ -0,0 +1,357 @@
+//! Command line handling shared by the binaries.
+
+use bufcli::{ClimoDB, ClimoQueryInterface};
+use bufkit_data::{Archive, Model};
+use clap::{App, Arg};
+use graphs::{
+    load_all_sites_and_models, load_for_model, load_site, plot_all, save_all, FwxError, Message,
+    OutputFormat,
+};
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+use strum::IntoEnumIterator;
+
+/// What to do with the loaded data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Make plots with gnuplot.
+    Plot,
+    /// Save the data in text files.
+    Save,
+}
+
+/// Default values for the command line options, these vary by binary.
+pub struct Defaults {
+    pub output: &'static str,
+    pub days_back: i64,
+    pub sites: &'static [&'static str],
+    pub models: &'static [Model],
+    pub format: Format,
+}
+
+/// The options supplied on the command line.
+pub struct CmdLineArgs {
+    pub archive: PathBuf,
+    pub output: String,
+    pub days_back: i64,
+    pub sites: Vec<String>,
+    pub models: Vec<Model>,
+    pub format: Format,
+    pub no_climo: bool,
+    #[cfg_attr(not(feature = "progress-bar"), allow(dead_code))]
+    pub quiet: bool,
+}
+
+/// Parse the command line.
+///
+/// If the archive is not supplied, the `BUFKIT_ARCHIVE` environment variable is used, and if that
+/// is not set then `~/bufkit` is used.
+pub fn parse_args(
+    name: &str,
+    about: &str,
+    defaults: &Defaults,
+) -> Result<CmdLineArgs, Box<dyn Error>> {
+    let matches = App::new(name)
+        .about(about)
+        .arg(
+            Arg::with_name("archive")
+                .long("archive")
+                .short("a")
+                .takes_value(true)
+                .help("Path to the bufkit archive, defaults to $BUFKIT_ARCHIVE or ~/bufkit."),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .short("o")
+                .takes_value(true)
+                .help("Directory to save the output in."),
+        )
+        .arg(
+            Arg::with_name("days-back")
+                .long("days-back")
+                .short("d")
+                .takes_value(true)
+                .help("Number of days of model runs to load."),
+        )
+        .arg(
+            Arg::with_name("site")
+                .long("site")
+                .short("s")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Site id to load, may be repeated. Defaults to all sites."),
+        )
+        .arg(
+            Arg::with_name("model")
+                .long("model")
+                .short("m")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .possible_values(&["gfs", "nam", "nam4km"])
+                .case_insensitive(true)
+                .help("Model to load, may be repeated. Defaults to all models."),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .alias("output-format")
+                .short("f")
+                .takes_value(true)
+                .possible_values(&["plot", "save"])
+                .case_insensitive(true)
+                .help("Make plots or save the data to text files."),
+        )
+        .arg(
+            Arg::with_name("no-climo")
+                .long("no-climo")
+                .help("Do not use the climatology database, even if it exists."),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .long("quiet")
+                .short("q")
+                .help("Do not show the progress bar."),
+        )
+        .get_matches();
+
+    let archive = archive_path(matches.value_of("archive"))?;
+
+    let output = matches
+        .value_of("output")
+        .unwrap_or(defaults.output)
+        .to_owned();
+
+    let days_back = match matches.value_of("days-back") {
+        Some(days_back) => days_back.parse()?,
+        None => defaults.days_back,
+    };
+
+    let sites: Vec<String> = match matches.values_of("site") {
+        Some(sites) => sites.map(|site| site.to_owned()).collect(),
+        None => defaults.sites.iter().map(|&site| site.to_owned()).collect(),
+    };
+
+    let models: Vec<Model> = match matches.values_of("model") {
+        Some(models) => models.filter_map(parse_model).collect(),
+        None => defaults.models.to_vec(),
+    };
+
+    let format = match matches.value_of("format") {
+        Some(format) if format.eq_ignore_ascii_case("save") => Format::Save,
+        Some(_) => Format::Plot,
+        None => defaults.format,
+    };
+
+    let no_climo = matches.is_present("no-climo");
+    let quiet = matches.is_present("quiet");
+
+    Ok(CmdLineArgs {
+        archive,
+        output,
+        days_back,
+        sites,
+        models,
+        format,
+        no_climo,
+        quiet,
+    })
+}
+
+/// Get the path to the archive, using the `BUFKIT_ARCHIVE` environment variable or `~/bufkit` if
+/// it was not supplied on the command line.
+pub fn archive_path(arg: Option<&str>) -> Result<PathBuf, Box<dyn Error>> {
+    let archive = match arg {
+        Some(archive) => PathBuf::from(archive),
+        None => match std::env::var_os("BUFKIT_ARCHIVE") {
+            Some(archive) => PathBuf::from(archive),
+            None => directories::UserDirs::new()
+                .ok_or("No home directory!")?
+                .home_dir()
+                .join("bufkit"),
+        },
+    };
+
+    Ok(archive)
+}
+
+/// Connect to the climatology database in the archive, unless it was disabled with `no_climo`.
+///
+/// If the connection fails a warning is printed and `None` is returned, so the plots and data are
+/// still made without the climatology.
+pub fn connect_climo(archive: &Path, no_climo: bool) -> Option<ClimoDB> {
+    if no_climo {
+        return None;
+    }
+
+    match ClimoDB::connect_or_create(archive) {
+        Ok(climo) => Some(climo),
+        Err(err) => {
+            eprintln!("Warning: continuing without climatology: {}", err);
+            None
+        }
+    }
+}
+
+/// Initialize the queries on the climatology database, printing a warning and returning `None` if
+/// that fails.
+pub fn climo_interface(climo: &ClimoDB) -> Option<ClimoQueryInterface<'_>> {
+    match ClimoQueryInterface::initialize(climo) {
+        Ok(climo) => Some(climo),
+        Err(err) => {
+            eprintln!("Warning: continuing without climatology: {}", err);
+            None
+        }
+    }
+}
+
+/// Load the data requested on the command line.
+///
+/// If no sites were requested, all the sites in the archive are loaded for the requested models,
+/// or for all models if none were requested. Otherwise each requested model is loaded for each
+/// requested site, with all models used if none were requested.
+pub fn load(arch: &Archive, args: &CmdLineArgs) -> Box<dyn Iterator<Item = Message> + Send> {
+    if args.sites.is_empty() && args.models.is_empty() {
+        return Box::new(load_all_sites_and_models(arch, args.days_back, None, None).into_iter());
+    }
+
+    if args.sites.is_empty() {
+        let receivers: Vec<_> = args
+            .models
+            .iter()
+            .map(|&model| load_for_model(arch, model, args.days_back, None))
+            .collect();
+
+        return Box::new(receivers.into_iter().flat_map(|recv| recv.into_iter()));
+    }
+
+    let models: Vec<Model> = if args.models.is_empty() {
+        Model::iter().collect()
+    } else {
+        args.models.clone()
+    };
+
+    let receivers: Vec<_> = args
+        .sites
+        .iter()
+        .flat_map(|site| {
+            models
+                .iter()
+                .map(move |&model| load_site(arch, site, model, args.days_back, None))
+        })
+        .collect();
+
+    Box::new(receivers.into_iter().flat_map(|recv| recv.into_iter()))
+}
+
+/// Show a progress bar that advances as each message is received.
+///
+/// The bar shows the total number of messages if it can be calculated from the archive, otherwise
+/// it is a spinner. It is hidden if `--quiet` was passed on the command line.
+#[cfg(feature = "progress-bar")]
+pub fn with_progress(
+    arch: &Archive,
+    args: &CmdLineArgs,
+    data: Box<dyn Iterator<Item = Message> + Send>,
+) -> Box<dyn Iterator<Item = Message> + Send> {
+    use indicatif::{ProgressBar, ProgressStyle};
+
+    if args.quiet {
+        return data;
+    }
+
+    let models: Vec<Model> = if args.models.is_empty() {
+        Model::iter().collect()
+    } else {
+        args.models.clone()
+    };
+
+    // One message is sent for each site and model.
+    let total: Option<usize> = if args.sites.is_empty() {
+        models
+            .iter()
+            .map(|&model| arch.sites_and_ids_for(model).map(|sites| sites.len()).ok())
+            .sum()
+    } else {
+        Some(args.sites.len() * models.len())
+    };
+
+    let pb = match total {
+        Some(total) => ProgressBar::new(total as u64).with_style(
+            ProgressStyle::default_bar()
+                .template("{bar:40} {pos}/{len} [{elapsed_precise} ETA {eta}] {wide_msg}"),
+        ),
+        None => ProgressBar::new_spinner().with_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner} {pos} [{elapsed_precise}] {wide_msg}"),
+        ),
+    };
+
+    Box::new(ProgressIter { data, pb })
+}
+
+/// An iterator that advances a progress bar for each item.
+#[cfg(feature = "progress-bar")]
+struct ProgressIter {
+    data: Box<dyn Iterator<Item = Message> + Send>,
+    pb: indicatif::ProgressBar,
+}
+
+#[cfg(feature = "progress-bar")]
+impl Iterator for ProgressIter {
+    type Item = Message;
+
+    fn next(&mut self) -> Option<Message> {
+        match self.data.next() {
+            Some(msg) => {
+                self.pb.set_message(msg.to_string());
+                self.pb.inc(1);
+                Some(msg)
+            }
+            None => {
+                self.pb.finish_and_clear();
+                None
+            }
+        }
+    }
+}
+
+/// Plot or save the data as requested on the command line.
+pub fn run(
+    data: Box<dyn Iterator<Item = Message> + Send>,
+    args: &CmdLineArgs,
+    climo: Option<ClimoQueryInterface>,
+) -> Result<(), Box<dyn Error>> {
+    match args.format {
+        Format::Plot => match plot_all(data, &args.output, climo, None, None, None) {
+            Err(err @ (FwxError::GnuplotNotFound | FwxError::GnuplotVersionTooOld { .. })) => {
+                println!("{}", err)
+            }
+            res => res?,
+        },
+        Format::Save => save_all(
+            data,
+            &args.output,
+            climo,
+            None,
+            OutputFormat::GnuplotDat,
+            None,
+            None,
+        )?,
+    }
+
+    Ok(())
+}
+
+pub fn parse_model(model: &str) -> Option<Model> {
+    match model.to_lowercase().as_str() {
+        "gfs" => Some(Model::GFS),
+        "nam" => Some(Model::NAM),
+        "nam4km" => Some(Model::NAM4KM),
+        _ => None,
+    }
+}
@@ -0,0 +1,104 @@
+use bufkit_data::{Archive, Model};
+use clap::{App, Arg};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+};
+use strum::IntoEnumIterator;
+
+#[allow(dead_code)]
+mod common;
+use common::{archive_path, parse_model};
+
+/// The information about a site to print in the table.
+struct SiteRow {
+    station_num: u32,
+    ids: BTreeSet<String>,
+    description: String,
+    models: Vec<&'static str>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let matches = App::new("fwx_list_sites")
+        .about("List the sites available in a bufkit archive.")
+        .arg(
+            Arg::with_name("archive")
+                .long("archive")
+                .short("a")
+                .takes_value(true)
+                .help("Path to the bufkit archive, defaults to $BUFKIT_ARCHIVE or ~/bufkit."),
+        )
+        .arg(
+            Arg::with_name("model")
+                .long("model")
+                .short("m")
+                .takes_value(true)
+                .possible_values(&["gfs", "nam", "nam4km"])
+                .case_insensitive(true)
+                .help("Only list sites available for this model."),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .long("sort")
+                .takes_value(true)
+                .possible_values(&["station", "id"])
+                .case_insensitive(true)
+                .default_value("station")
+                .help("Sort by station number or alphabetically by site id."),
+        )
+        .get_matches();
+
+    let arch = Archive::connect(&archive_path(matches.value_of("archive"))?)?;
+
+    let models: Vec<Model> = match matches.value_of("model").and_then(parse_model) {
+        Some(model) => vec![model],
+        None => Model::iter().collect(),
+    };
+
+    let mut sites: BTreeMap<u32, SiteRow> = BTreeMap::new();
+    for model in models {
+        for (site_info, site_id) in arch.sites_and_ids_for(model)? {
+            let station_num = u32::from(site_info.station_num);
+            let row = sites.entry(station_num).or_insert_with(|| SiteRow {
+                station_num,
+                ids: BTreeSet::new(),
+                description: site_info.description(),
+                models: vec![],
+            });
+
+            row.ids.insert(site_id);
+            row.models.push(model.as_static_str());
+        }
+    }
+
+    let mut rows: Vec<SiteRow> = sites.into_values().collect();
+    if matches
+        .value_of("sort")
+        .map(|sort| sort.eq_ignore_ascii_case("id"))
+        .unwrap_or(false)
+    {
+        rows.sort_by(|a, b| a.ids.iter().next().cmp(&b.ids.iter().next()));
+    }
+
+    println!(
+        "{:>11} | {:<12} | {:<40} | available_models",
+        "station_num", "site_id", "description"
+    );
+    for SiteRow {
+        station_num,
+        ids,
+        description,
+        models,
+    } in rows
+    {
+        println!(
+            "{:>11} | {:<12} | {:<40} | {}",
+            station_num,
+            ids.into_iter().collect::<Vec<_>>().join(","),
+            description,
+            models.join(",")
+        );
+    }
+
+    Ok(())
+}
@@ -1,23 +1,31 @@
-use bufcli::{ClimoDB, ClimoQueryInterface};
 use bufkit_data::Archive;
-use graphs::{load_all_sites_and_models, plot_all};
 use std::error::Error;
 
-const DAYS_BACK: i64 = 2;
+mod common;
+use common::{climo_interface, connect_climo, load, parse_args, run, Defaults, Format};
+
+const DEFAULTS: Defaults = Defaults {
+    output: "images",
+    days_back: 2,
+    sites: &[],
+    models: &[],
+    format: Format::Plot,
+};
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let home_dir = directories::UserDirs::new()
-        .expect("No home directory!")
-        .home_dir()
-        .to_owned();
-    let archive = home_dir.join("bufkit");
-    let arch = Archive::connect(&archive)?;
-    let climo = ClimoDB::connect_or_create(&archive)?;
-    let climo = ClimoQueryInterface::initialize(&climo)?;
+    let args = parse_args(
+        "plot_all",
+        "Plot fire weather parameters for sites in a bufkit archive.",
+        &DEFAULTS,
+    )?;
 
-    let string_data = load_all_sites_and_models(&arch, DAYS_BACK).into_iter();
+    let arch = Archive::connect(&args.archive)?;
+    let climo = connect_climo(&args.archive, args.no_climo);
+    let climo = climo.as_ref().and_then(climo_interface);
 
-    plot_all(string_data, "images", Some(climo));
+    let string_data = load(&arch, &args);
+    #[cfg(feature = "progress-bar")]
+    let string_data = common::with_progress(&arch, &args, string_data);
 
-    Ok(())
+    run(string_data, &args, climo)
 }
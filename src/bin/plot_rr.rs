@@ -1,10 +1,13 @@
 #![type_length_limit = "1115086"]
-use bufcli::{ClimoDB, ClimoQueryInterface};
-use bufkit_data::{Archive, Model, SiteInfo, StationNumber};
+use bufkit_data::{Archive, Model};
 use chrono::{Duration, NaiveDate};
-use graphs::{load_for_site_and_date_and_time, load_from_files, plot_all, FileData};
+use graphs::{load_for_site_and_date_and_time, load_from_files, plot_all, FileDataBuilder};
 use std::error::Error;
 
+#[allow(dead_code)]
+mod common;
+use common::{climo_interface, connect_climo};
+
 const DAYS_BACK: i64 = 4;
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -14,8 +17,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         .to_owned();
     let archive = home_dir.join("bufkit");
     let arch = Archive::connect(&archive)?;
-    let climo = ClimoDB::connect_or_create(&archive)?;
-    let climo = ClimoQueryInterface::initialize(&climo)?;
+    let climo = connect_climo(&archive, false);
+    let climo = climo.as_ref().and_then(climo_interface);
 
     let now = NaiveDate::from_ymd(2017, 9, 2).and_hms(12, 0, 0);
 
@@ -31,153 +34,125 @@ fn main() -> Result<(), Box<dyn Error>> {
         .join("Bufkit");
 
     let file_data = vec![
-        FileData {
-            site: SiteInfo {
-                name: Some("KRR1".to_owned()),
-                station_num: StationNumber::from(1),
-                notes: None,
-                time_zone: None,
-                state: None,
-                auto_download: false,
-            },
-            model: "LocalWrf".to_owned(),
-            start: start_files,
-            end: end_files,
-            files: vec![
+        FileDataBuilder::new()
+            .site_id("KRR1")
+            .station_num(1)
+            .model("LocalWrf")
+            .start(start_files)
+            .end(end_files)
+            .add_file(
                 research_root
                     .join("local_arw_krr1")
                     .join("2017090212.arw_krr1.buf"),
+            )
+            .add_file(
                 research_root
                     .join("local_arw_krr1")
                     .join("2017090312.arw_krr1.buf"),
-            ],
-        },
-        FileData {
-            site: SiteInfo {
-                name: Some("KRR2".to_owned()),
-                station_num: StationNumber::from(2),
-                notes: None,
-                time_zone: None,
-                state: None,
-                auto_download: false,
-            },
-            model: "LocalWrf".to_owned(),
-            start: start_files,
-            end: end_files,
-            files: vec![
+            )
+            .build()?,
+        FileDataBuilder::new()
+            .site_id("KRR2")
+            .station_num(2)
+            .model("LocalWrf")
+            .start(start_files)
+            .end(end_files)
+            .add_file(
                 research_root
                     .join("local_arw_krr2")
                     .join("2017090212.arw_krr2.buf"),
+            )
+            .add_file(
                 research_root
                     .join("local_arw_krr2")
                     .join("2017090312.arw_krr2.buf"),
-            ],
-        },
-        FileData {
-            site: SiteInfo {
-                name: Some("KRR3".to_owned()),
-                station_num: StationNumber::from(3),
-                notes: None,
-                time_zone: None,
-                state: None,
-                auto_download: false,
-            },
-            model: "LocalWrf".to_owned(),
-            start: start_files,
-            end: end_files,
-            files: vec![
+            )
+            .build()?,
+        FileDataBuilder::new()
+            .site_id("KRR3")
+            .station_num(3)
+            .model("LocalWrf")
+            .start(start_files)
+            .end(end_files)
+            .add_file(
                 research_root
                     .join("local_arw_krr3")
                     .join("2017090212.arw_krr3.buf"),
+            )
+            .add_file(
                 research_root
                     .join("local_arw_krr3")
                     .join("2017090312.arw_krr3.buf"),
-            ],
-        },
-        FileData {
-            site: SiteInfo {
-                name: Some("KRR4".to_owned()),
-                station_num: StationNumber::from(4),
-                notes: None,
-                time_zone: None,
-                state: None,
-                auto_download: false,
-            },
-            model: "LocalWrf".to_owned(),
-            start: start_files,
-            end: end_files,
-            files: vec![
+            )
+            .build()?,
+        FileDataBuilder::new()
+            .site_id("KRR4")
+            .station_num(4)
+            .model("LocalWrf")
+            .start(start_files)
+            .end(end_files)
+            .add_file(
                 research_root
                     .join("local_arw_krr4")
                     .join("2017090212.arw_krr4.buf"),
+            )
+            .add_file(
                 research_root
                     .join("local_arw_krr4")
                     .join("2017090312.arw_krr4.buf"),
-            ],
-        },
-        FileData {
-            site: SiteInfo {
-                name: Some("KRR5".to_owned()),
-                station_num: StationNumber::from(5),
-                notes: None,
-                time_zone: None,
-                state: None,
-                auto_download: false,
-            },
-            model: "LocalWrf".to_owned(),
-            start: start_files,
-            end: end_files,
-            files: vec![
+            )
+            .build()?,
+        FileDataBuilder::new()
+            .site_id("KRR5")
+            .station_num(5)
+            .model("LocalWrf")
+            .start(start_files)
+            .end(end_files)
+            .add_file(
                 research_root
                     .join("local_arw_krr5")
                     .join("2017090212.arw_krr5.buf"),
+            )
+            .add_file(
                 research_root
                     .join("local_arw_krr5")
                     .join("2017090312.arw_krr5.buf"),
-            ],
-        },
-        FileData {
-            site: SiteInfo {
-                name: Some("KSEE".to_owned()),
-                station_num: StationNumber::from(6),
-                notes: None,
-                time_zone: None,
-                state: None,
-                auto_download: false,
-            },
-            model: "LocalWrf".to_owned(),
-            start: start_files,
-            end: end_files,
-            files: vec![
+            )
+            .build()?,
+        FileDataBuilder::new()
+            .site_id("KSEE")
+            .station_num(6)
+            .model("LocalWrf")
+            .start(start_files)
+            .end(end_files)
+            .add_file(
                 research_root
                     .join("local_arw_ksee")
                     .join("2017090212.arw_ksee.buf"),
+            )
+            .add_file(
                 research_root
                     .join("local_arw_ksee")
                     .join("2017090312.arw_ksee.buf"),
-            ],
-        },
-        FileData {
-            site: SiteInfo {
-                name: Some("KMSO".to_owned()),
-                station_num: StationNumber::from(727730),
-                notes: None,
-                time_zone: None,
-                state: None,
-                auto_download: false,
-            },
-            model: "LocalWrf".to_owned(),
-            start: start_files,
-            end: end_files,
-            files: vec![
+            )
+            .build()?,
+        FileDataBuilder::new()
+            .site_id("KMSO")
+            .station_num(727730)
+            .model("LocalWrf")
+            .start(start_files)
+            .end(end_files)
+            .add_file(
                 research_root
                     .join("local_arw_kmso")
                     .join("2017090212.arw_kmso.buf"),
+            )
+            .add_file(
                 research_root
                     .join("local_arw_kmso")
                     .join("2017090312.arw_kmso.buf"),
-            ],
-        },
+            )
+            .build()?,
     ];
 
     let file_strings = file_data
@@ -185,28 +160,32 @@ fn main() -> Result<(), Box<dyn Error>> {
         .map(load_from_files)
         .flat_map(|chan| chan.into_iter());
 
-    let string_data = load_for_site_and_date_and_time(&arch, "kmso", Model::GFS, now, DAYS_BACK)
-        .into_iter()
-        .chain(
-            load_for_site_and_date_and_time(&arch, "kmso", Model::NAM, now, DAYS_BACK).into_iter(),
-        )
-        .chain(
-            load_for_site_and_date_and_time(&arch, "kmso", Model::NAM4KM, now, DAYS_BACK)
-                .into_iter(),
-        )
-        .chain(
-            load_for_site_and_date_and_time(&arch, "c18", Model::GFS, now, DAYS_BACK).into_iter(),
-        )
-        .chain(
-            load_for_site_and_date_and_time(&arch, "c18", Model::NAM, now, DAYS_BACK).into_iter(),
-        )
-        .chain(
-            load_for_site_and_date_and_time(&arch, "c18", Model::NAM4KM, now, DAYS_BACK)
-                .into_iter(),
-        )
-        .chain(file_strings);
+    let string_data =
+        load_for_site_and_date_and_time(&arch, "kmso", Model::GFS, now, DAYS_BACK, None)
+            .into_iter()
+            .chain(
+                load_for_site_and_date_and_time(&arch, "kmso", Model::NAM, now, DAYS_BACK, None)
+                    .into_iter(),
+            )
+            .chain(
+                load_for_site_and_date_and_time(&arch, "kmso", Model::NAM4KM, now, DAYS_BACK, None)
+                    .into_iter(),
+            )
+            .chain(
+                load_for_site_and_date_and_time(&arch, "c18", Model::GFS, now, DAYS_BACK, None)
+                    .into_iter(),
+            )
+            .chain(
+                load_for_site_and_date_and_time(&arch, "c18", Model::NAM, now, DAYS_BACK, None)
+                    .into_iter(),
+            )
+            .chain(
+                load_for_site_and_date_and_time(&arch, "c18", Model::NAM4KM, now, DAYS_BACK, None)
+                    .into_iter(),
+            )
+            .chain(file_strings);
 
-    plot_all(string_data, "images", Some(climo));
+    plot_all(string_data, "images", climo, None, None, None)?;
 
     Ok(())
 }
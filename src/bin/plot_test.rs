@@ -1,23 +1,31 @@
-use bufcli::{ClimoDB, ClimoQueryInterface};
 use bufkit_data::{Archive, Model};
-use graphs::{load_site, plot_all};
 use std::error::Error;
 
-const DAYS_BACK: i64 = 2;
+mod common;
+use common::{climo_interface, connect_climo, load, parse_args, run, Defaults, Format};
+
+const DEFAULTS: Defaults = Defaults {
+    output: "images",
+    days_back: 2,
+    sites: &["KTUS"],
+    models: &[Model::GFS],
+    format: Format::Plot,
+};
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let home_dir = directories::UserDirs::new()
-        .expect("No home directory!")
-        .home_dir()
-        .to_owned();
-    let archive = home_dir.join("bufkit");
-    let arch = Archive::connect(&archive)?;
-    let climo = ClimoDB::connect_or_create(&archive)?;
-    let climo = ClimoQueryInterface::initialize(&climo)?;
+    let args = parse_args(
+        "plot_test",
+        "Plot fire weather parameters for a test site.",
+        &DEFAULTS,
+    )?;
 
-    let loaded_files = load_site(&arch, "KTUS", Model::GFS, DAYS_BACK).into_iter();
+    let arch = Archive::connect(&args.archive)?;
+    let climo = connect_climo(&args.archive, args.no_climo);
+    let climo = climo.as_ref().and_then(climo_interface);
 
-    plot_all(loaded_files, "images", Some(climo));
+    let string_data = load(&arch, &args);
+    #[cfg(feature = "progress-bar")]
+    let string_data = common::with_progress(&arch, &args, string_data);
 
-    Ok(())
+    run(string_data, &args, climo)
 }
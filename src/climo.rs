@@ -0,0 +1,122 @@
+//! Access to the climatology database with caching of the query results.
+use bufcli::{ClimoElement, ClimoQueryInterface, Deciles};
+use bufkit_data::{SiteInfo, StationNumber};
+use chrono::NaiveDateTime;
+use std::{collections::HashMap, error::Error};
+
+/// The deciles for each hour in a time range.
+pub type HourlyDeciles = Vec<(NaiveDateTime, Deciles)>;
+
+/// A source of climatology data, like a `ClimoQueryInterface` or a `Climatology`.
+pub trait ClimoSource {
+    /// Get the deciles of `element` for each hour from `start` to `end` for the site and model.
+    fn hourly_deciles(
+        &mut self,
+        site: &SiteInfo,
+        model: &str,
+        element: ClimoElement,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> Result<HourlyDeciles, Box<dyn Error>>;
+}
+
+impl ClimoSource for ClimoQueryInterface<'_> {
+    fn hourly_deciles(
+        &mut self,
+        site: &SiteInfo,
+        model: &str,
+        element: ClimoElement,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> Result<HourlyDeciles, Box<dyn Error>> {
+        ClimoQueryInterface::hourly_deciles(self, site, model, element, start, end)
+    }
+}
+
+/// A `ClimoQueryInterface` that keeps the results of its queries in memory.
+///
+/// When a site, model, and element are requested again, the results are served from the cache if
+/// the time range was already queried. Otherwise the database is queried for a range covering
+/// both the new and the cached ranges, and that replaces the cached results.
+pub struct Climatology<'a> {
+    iface: ClimoQueryInterface<'a>,
+    cache: HashMap<CacheKey, CachedDeciles>,
+}
+
+type CacheKey = (StationNumber, String, ClimoElement);
+
+struct CachedDeciles {
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    deciles: HourlyDeciles,
+}
+
+impl<'a> Climatology<'a> {
+    /// Wrap a `ClimoQueryInterface` with an empty cache.
+    pub fn new(iface: ClimoQueryInterface<'a>) -> Self {
+        Climatology {
+            iface,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Remove all the cached results.
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Unwrap the `ClimoQueryInterface`, discarding the cache.
+    pub fn into_inner(self) -> ClimoQueryInterface<'a> {
+        self.iface
+    }
+}
+
+impl ClimoSource for Climatology<'_> {
+    fn hourly_deciles(
+        &mut self,
+        site: &SiteInfo,
+        model: &str,
+        element: ClimoElement,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> Result<HourlyDeciles, Box<dyn Error>> {
+        let key = (site.station_num, model.to_owned(), element);
+
+        let (query_start, query_end) = match self.cache.get(&key) {
+            Some(cached) if cached.start <= start && end <= cached.end => {
+                return Ok(in_range(&cached.deciles, start, end));
+            }
+            Some(cached) => (cached.start.min(start), cached.end.max(end)),
+            None => (start, end),
+        };
+
+        let deciles = self
+            .iface
+            .hourly_deciles(site, model, element, query_start, query_end)?;
+        let result = in_range(&deciles, start, end);
+
+        self.cache.insert(
+            key,
+            CachedDeciles {
+                start: query_start,
+                end: query_end,
+                deciles,
+            },
+        );
+
+        Ok(result)
+    }
+}
+
+/// Copy the deciles with valid times from `start` to `end`, inclusive.
+fn in_range(
+    deciles: &[(NaiveDateTime, Deciles)],
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> HourlyDeciles {
+    deciles
+        .iter()
+        .filter(|(valid_time, _)| start <= *valid_time && *valid_time <= end)
+        .cloned()
+        .collect()
+}
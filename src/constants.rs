@@ -0,0 +1,4 @@
+//! Constants shared between modules.
+
+/// The date format used in gnuplot data and scripts, and for displaying dates.
+pub(crate) const GP_DATE_FORMAT: &str = "%Y-%m-%d-%H";
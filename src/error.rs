@@ -0,0 +1,123 @@
+//! The error type for this crate.
+
+use bufkit_data::BufkitDataErr;
+use std::{error::Error, fmt::Display};
+
+/// The errors that may occur while loading, analyzing, saving, or plotting data.
+#[derive(Debug)]
+pub enum FwxError {
+    /// Unable to start the gnuplot process.
+    GnuplotLaunch(std::io::Error),
+    /// The gnuplot executable is not in the PATH.
+    GnuplotNotFound,
+    /// The installed gnuplot is too old for the plot templates, versions are (major, minor).
+    GnuplotVersionTooOld {
+        found: (u32, u32),
+        required: (u32, u32),
+    },
+    /// An error accessing the bufkit archive.
+    ArchiveAccess(BufkitDataErr),
+    /// An error reading or writing a file or pipe.
+    Io(std::io::Error),
+    /// There was no data available for the site and model.
+    NoData { site: String, model: String },
+    /// A gnuplot script is missing an inline data block it must use.
+    InvalidTemplate {
+        name: &'static str,
+        missing: &'static str,
+    },
+    /// Data for different sites was combined where it should all be for the same site.
+    SiteMismatch { expected: String, found: String },
+    /// There were no ensembles to combine.
+    NoEnsembles,
+    /// An error writing a NetCDF file.
+    #[cfg(feature = "netcdf")]
+    NetCdf(netcdf::Error),
+    /// An error parsing a TOML configuration file.
+    #[cfg(feature = "serde")]
+    Toml(toml::de::Error),
+}
+
+impl Display for FwxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use FwxError::*;
+
+        match self {
+            GnuplotLaunch(err) => write!(f, "unable to launch gnuplot: {}", err),
+            GnuplotNotFound => write!(
+                f,
+                "gnuplot not found in PATH; install it or use save_all instead"
+            ),
+            GnuplotVersionTooOld { found, required } => write!(
+                f,
+                "gnuplot {}.{} is too old, version {}.{} or newer is required",
+                found.0, found.1, required.0, required.1
+            ),
+            ArchiveAccess(err) => write!(f, "error accessing the archive: {}", err),
+            Io(err) => write!(f, "i/o error: {}", err),
+            NoData { site, model } => write!(f, "no data available for {} at {}", model, site),
+            InvalidTemplate { name, missing } => {
+                write!(f, "the {} plot template does not use {}", name, missing)
+            }
+            SiteMismatch { expected, found } => {
+                write!(
+                    f,
+                    "expected data for {} but found data for {}",
+                    expected, found
+                )
+            }
+            NoEnsembles => write!(f, "there are no ensembles to combine"),
+            #[cfg(feature = "netcdf")]
+            NetCdf(err) => write!(f, "netcdf error: {}", err),
+            #[cfg(feature = "serde")]
+            Toml(err) => write!(f, "toml error: {}", err),
+        }
+    }
+}
+
+impl Error for FwxError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use FwxError::*;
+
+        match self {
+            GnuplotLaunch(err) | Io(err) => Some(err),
+            ArchiveAccess(err) => Some(err),
+            GnuplotNotFound
+            | GnuplotVersionTooOld { .. }
+            | NoData { .. }
+            | InvalidTemplate { .. }
+            | SiteMismatch { .. }
+            | NoEnsembles => None,
+            #[cfg(feature = "netcdf")]
+            NetCdf(err) => Some(err),
+            #[cfg(feature = "serde")]
+            Toml(err) => Some(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for FwxError {
+    fn from(err: std::io::Error) -> Self {
+        FwxError::Io(err)
+    }
+}
+
+impl From<BufkitDataErr> for FwxError {
+    fn from(err: BufkitDataErr) -> Self {
+        FwxError::ArchiveAccess(err)
+    }
+}
+
+#[cfg(feature = "netcdf")]
+impl From<netcdf::Error> for FwxError {
+    fn from(err: netcdf::Error) -> Self {
+        FwxError::NetCdf(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<toml::de::Error> for FwxError {
+    fn from(err: toml::de::Error) -> Self {
+        FwxError::Toml(err)
+    }
+}
@@ -0,0 +1,276 @@
+//! Export analyzed data to formats for other tools.
+use crate::{error::FwxError, timeseries::MergedSeries, types::AnalyzedData};
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime};
+#[cfg(feature = "netcdf")]
+use metfor::Quantity;
+#[cfg(feature = "netcdf")]
+use std::path::Path;
+use std::{collections::BTreeMap, io::Write};
+
+/// The default fill value for floating point data in the CF conventions.
+#[cfg(feature = "netcdf")]
+const FILL_VALUE_F64: f64 = 9.969_209_968_386_869e36;
+/// The default fill value for integer data in the CF conventions.
+#[cfg(feature = "netcdf")]
+const FILL_VALUE_I32: i32 = -2_147_483_647;
+
+/// Write a `MergedSeries` to a CF compliant NetCDF file.
+///
+/// The file has a single `time` dimension in seconds since 1970-01-01. Missing values are set to
+/// the fill value, and the site and model information are stored as global attributes.
+#[cfg(feature = "netcdf")]
+pub fn write_netcdf(path: &Path, series: &MergedSeries<AnalyzedData>) -> Result<(), FwxError> {
+    let MergedSeries { meta, data } = series;
+    let data = data.as_ref();
+
+    let fill_nan = |val: f64| if val.is_nan() { FILL_VALUE_F64 } else { val };
+
+    let mut file = netcdf::create(path)?;
+
+    file.add_attribute("Conventions", "CF-1.8")?;
+    file.add_attribute("featureType", "timeSeries")?;
+    file.add_attribute("station_num", u32::from(meta.site.station_num))?;
+    file.add_attribute("site", meta.site.description())?;
+    file.add_attribute("model", meta.model.as_str())?;
+    file.add_attribute("start", meta.start.to_string())?;
+    file.add_attribute("now", meta.now.to_string())?;
+    file.add_attribute("end", meta.end.to_string())?;
+
+    file.add_dimension("time", data.len())?;
+
+    let times: Vec<f64> = data
+        .iter()
+        .map(|anal| anal.valid_time.and_utc().timestamp() as f64)
+        .collect();
+    let mut var = file.add_variable::<f64>("time", &["time"])?;
+    var.put_attribute("units", "seconds since 1970-01-01 00:00:00")?;
+    var.put_attribute("long_name", "valid time")?;
+    var.put_attribute("standard_name", "time")?;
+    var.put_attribute("calendar", "standard")?;
+    var.put_values(&times, ..)?;
+
+    let f64_vars: [(&str, &str, &str, Vec<f64>); 3] = [
+        (
+            "hdw",
+            "1",
+            "Hot-Dry-Windy Index",
+            data.iter().map(|anal| fill_nan(anal.hdw)).collect(),
+        ),
+        (
+            "blow_up_dt",
+            "K",
+            "temperature change required for a blow up",
+            data.iter()
+                .map(|anal| fill_nan(anal.blow_up_dt.unpack()))
+                .collect(),
+        ),
+        (
+            "blow_up_height",
+            "m",
+            "change in plume height at blow up",
+            data.iter()
+                .map(|anal| fill_nan(anal.blow_up_height.unpack()))
+                .collect(),
+        ),
+    ];
+
+    for (name, units, long_name, vals) in f64_vars.iter() {
+        let mut var = file.add_variable::<f64>(name, &["time"])?;
+        var.set_fill_value(FILL_VALUE_F64)?;
+        var.put_attribute("units", *units)?;
+        var.put_attribute("long_name", *long_name)?;
+        var.put_values(vals, ..)?;
+    }
+
+    let lead_times: Vec<i32> = data.iter().map(|anal| anal.lead_time).collect();
+    let mut var = file.add_variable::<i32>("lead_time", &["time"])?;
+    var.set_fill_value(FILL_VALUE_I32)?;
+    var.put_attribute("units", "hours")?;
+    var.put_attribute("long_name", "forecast lead time")?;
+    var.put_values(&lead_times, ..)?;
+
+    Ok(())
+}
+
+/// Write the daily extremes in a plain fixed width text format.
+///
+/// The column layout is specific to this crate, it is meant to be easy to read by eye or to load
+/// into a spreadsheet or another tool with a fixed width parser.
+///
+/// The hourly values are grouped by local calendar day using the site's time zone, or UTC if the
+/// site doesn't have one. There is one row per day with the columns:
+///
+/// | Columns | Width | Value                                         |
+/// |---------|-------|-----------------------------------------------|
+/// | 1-6     | 6     | station number                                |
+/// | 7-14    | 8     | local date, `YYYYMMDD`                        |
+/// | 15-18   | 4     | maximum HDW                                   |
+/// | 19-22   | 4     | local time of the maximum HDW, `HHMM`         |
+/// | 23-25   | 3     | minimum relative humidity [%]                 |
+/// | 26-29   | 4     | local time of the minimum RH, `HHMM`          |
+/// | 30-33   | 4     | maximum temperature [°F]                      |
+/// | 34-37   | 4     | local time of the maximum temperature, `HHMM` |
+///
+/// Numbers are right aligned, and missing values are left blank.
+pub fn export_daily_extremes<W: Write>(
+    series: &MergedSeries<AnalyzedData>,
+    dest: &mut W,
+) -> Result<(), FwxError> {
+    let MergedSeries { meta, data } = series;
+
+    let offset = meta
+        .site
+        .time_zone
+        .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+
+    let mut days: BTreeMap<NaiveDate, DailyExtremes> = BTreeMap::new();
+    for anal in data.iter() {
+        let local_time = anal.valid_time + offset;
+        days.entry(local_time.date())
+            .or_default()
+            .update(local_time, anal);
+    }
+
+    let blank_or = |val: Option<(f64, NaiveDateTime)>, width: usize| match val {
+        Some((val, time)) => format!("{:>width$.0}{}", val, time.format("%H%M"), width = width),
+        None => format!("{:width$}", "", width = width + 4),
+    };
+
+    for (day, extremes) in days {
+        writeln!(
+            dest,
+            "{:>6}{}{}{}{}",
+            u32::from(meta.site.station_num),
+            day.format("%Y%m%d"),
+            blank_or(extremes.max_hdw, 4),
+            blank_or(extremes.min_rh, 3),
+            blank_or(
+                extremes
+                    .max_temperature_c
+                    .map(|(t, time)| (t * 9.0 / 5.0 + 32.0, time)),
+                4
+            ),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The extreme values for a single day along with the local time they occurred.
+#[derive(Default)]
+struct DailyExtremes {
+    max_hdw: Option<(f64, NaiveDateTime)>,
+    min_rh: Option<(f64, NaiveDateTime)>,
+    max_temperature_c: Option<(f64, NaiveDateTime)>,
+}
+
+impl DailyExtremes {
+    /// Update the extremes with the values at `local_time`, NaN values are skipped.
+    fn update(&mut self, local_time: NaiveDateTime, anal: &AnalyzedData) {
+        let update =
+            |current: &mut Option<(f64, NaiveDateTime)>, val: f64, better: fn(f64, f64) -> bool| {
+                if val.is_nan() {
+                    return;
+                }
+
+                match current {
+                    Some((cur, _)) if !better(val, *cur) => {}
+                    _ => *current = Some((val, local_time)),
+                }
+            };
+
+        update(&mut self.max_hdw, anal.hdw, |a, b| a > b);
+        update(&mut self.min_rh, anal.surface_rh, |a, b| a < b);
+        update(
+            &mut self.max_temperature_c,
+            anal.surface_temperature_c,
+            |a, b| a > b,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timeseries::MetaData;
+    use bufkit_data::{SiteInfo, StationNumber};
+
+    fn time(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2020, 8, day)
+            .and_then(|date| date.and_hms_opt(hour, 0, 0))
+            .unwrap()
+    }
+
+    fn anal(valid_time: NaiveDateTime, hdw: f64, rh: f64, temperature_c: f64) -> AnalyzedData {
+        AnalyzedData {
+            surface_rh: rh,
+            surface_temperature_c: temperature_c,
+            ..AnalyzedData::with_hdw(valid_time, hdw)
+        }
+    }
+
+    fn series(
+        time_zone: Option<FixedOffset>,
+        data: Vec<AnalyzedData>,
+    ) -> MergedSeries<AnalyzedData> {
+        MergedSeries {
+            meta: MetaData {
+                site: SiteInfo {
+                    station_num: StationNumber::from(727_730),
+                    name: Some("kmso".to_owned()),
+                    notes: None,
+                    state: None,
+                    auto_download: false,
+                    time_zone,
+                },
+                model: "GFS".to_owned(),
+                start: time(1, 0),
+                now: time(1, 0),
+                end: time(2, 0),
+            },
+            data: data.into_iter().collect(),
+        }
+    }
+
+    fn export(series: &MergedSeries<AnalyzedData>) -> String {
+        let mut buf = Vec::new();
+        export_daily_extremes(series, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn one_day_of_extremes() {
+        let series = series(
+            None,
+            vec![
+                anal(time(1, 12), 150.0, 35.0, 20.0),
+                anal(time(1, 15), std::f64::NAN, 18.0, 28.0),
+                anal(time(1, 18), 321.4, 12.0, 30.0),
+                anal(time(1, 21), 200.0, 20.0, 25.0),
+            ],
+        );
+
+        assert_eq!(export(&series), "72773020200801 3211800 121800  861800\n");
+    }
+
+    #[test]
+    fn days_are_local_and_missing_values_are_blank() {
+        let mdt = FixedOffset::west_opt(6 * 3600);
+        let series = series(
+            mdt,
+            vec![
+                // 2020-08-01 21Z is 15 local on the 1st, 2020-08-02 03Z is 21 local on the 1st.
+                anal(time(1, 21), 100.0, std::f64::NAN, 30.0),
+                anal(time(2, 3), 50.0, std::f64::NAN, 25.0),
+                // 2020-08-02 06Z is 00 local on the 2nd.
+                anal(time(2, 6), std::f64::NAN, 40.0, std::f64::NAN),
+            ],
+        );
+
+        assert_eq!(
+            export(&series),
+            "72773020200801 1001500         861500\n\
+             72773020200802         400000        \n"
+        );
+    }
+}
@@ -2,26 +2,89 @@
 // API
 //
 pub use crate::{
-    messages::Message,
-    plot::{plot_all, save_all},
+    analysis::{extract_diurnal_cycle, DiurnalCycle},
+    climo::{Climatology, ClimoSource, HourlyDeciles},
+    error::FwxError,
+    export::export_daily_extremes,
+    messages::{ContextualError, Message},
+    plot::{
+        check_gnuplot_version, is_gnuplot_available, meta_data_header_string, plot_all,
+        plot_multi_site, plot_with_templates, save_all, save_all_parallel,
+        write_all_climo_elements, write_ensemble_data, write_ensemble_data_tabular,
+        write_meta_data_header, write_summary_stats, ErrorHandler, FileNameTemplate, OutputFormat,
+        MIN_GNUPLOT_VERSION,
+    },
+    report::generate_html_report,
     sources::{
-        load_all_sites_and_models, load_for_site_and_date_and_time, load_from_files, load_site,
-        FileData,
+        count_messages, drain_receiver, drain_receiver_with_timeout, from_messages,
+        load_all_sites_and_models, load_all_sites_and_models_filtered,
+        load_all_sites_and_models_with_progress, load_for_model, load_for_site_and_date_and_time,
+        load_from_directory, load_from_files, load_range, load_site, load_site_all_models,
+        load_specific_runs, summarize_archive, ArchiveSummary, BufkitStringCache, FileData,
+        FileDataBuilder, ForecastHorizons, ModelSummary, DRAIN_TIMEOUT,
+    },
+    stats::quantile,
+    timeseries::{
+        concat, merge_across_models, EnsembleList, EnsembleSeries, EnsembleSeriesBuilder,
+        Interpolatable, MergedSeries, MetaData, ModelTimes, TimeSeries, ValidTime,
     },
+    types::{
+        check_thresholds, parse_sounding, parse_soundings_from_str, AnalysisConfig, AnalyzedData,
+        AnalyzedRecord, EnsembleFieldStats, MergeStrategy, SpreadStats, ThresholdExceedance,
+        TimeStepStats,
+    },
+    validation::{validate, ValidationReport},
+    verification::{verify, FieldVerification, VerificationStats},
 };
 
+#[cfg(feature = "netcdf")]
+pub use crate::export::write_netcdf;
+#[cfg(feature = "svg-backend")]
+pub use crate::plot::{plot_all_svg, save_all_svg};
+#[cfg(feature = "serde")]
+pub use crate::sources::load_file_data_from_toml;
+#[cfg(feature = "http")]
+pub use crate::sources::{load_from_url, load_from_urls};
+
 //
 // Internal implementation details.
 //
+/// Macros for optional `tracing` instrumentation, must be first so the macros are available in
+/// the other modules.
+#[macro_use]
+mod trace;
+/// Analyses summarizing a whole time series, like the diurnal cycle.
+mod analysis;
+/// Cached access to the climatology database.
+mod climo;
+/// Constants shared between modules.
+mod constants;
+/// The error type for this crate.
+mod error;
+/// Export to file formats for other tools.
+mod export;
 /// Messages for carrying information between the loading and plotting functions.
 mod messages;
 /// Types and functions for plotting
 mod plot;
+/// Self-contained HTML reports of the plots.
+mod report;
+/// Helpers for serializing and deserializing with serde.
+#[cfg(feature = "serde")]
+mod serialize;
 /// Functions for loading data from an archive or files.
 mod sources;
+/// Statistics calculated from the model data.
+mod stats;
 /// Time series concepts such as `EnsembleList` and `TimeSeries` and transforms for applied
 /// to those objects and for converting between them.
 mod timeseries;
-/// Types, like, `AnalyzedData`, `CapePartion` that are typically stored in
-/// `TimeSeries`and the transformations between them.
+/// Types, like, `AnalyzedData`, that are typically stored in `TimeSeries` and the
+/// transformations between them.
 mod types;
+/// Small helpers shared between modules.
+mod util;
+/// Data quality checks.
+mod validation;
+/// Verification of the forecasts against observations.
+mod verification;
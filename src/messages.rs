@@ -1,5 +1,7 @@
 use crate::sources::StringData;
 use bufkit_data::BufkitDataErr;
+use chrono::NaiveDateTime;
+use std::{error::Error, fmt::Display};
 
 pub struct Message(InnerMessage);
 
@@ -7,6 +9,29 @@ impl Message {
     pub(crate) fn payload(self) -> InnerMessage {
         self.0
     }
+
+    /// Get the error if this message is about a failure to load data.
+    pub fn error(&self) -> Option<&ContextualError> {
+        match &self.0 {
+            InnerMessage::StringData(_) => None,
+            InnerMessage::BufkitDataError(err) => Some(err),
+        }
+    }
+}
+
+impl Display for Message {
+    /// A short description of what the message is about, e.g. `GFS for Missoula, MT (KMSO)`.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.0 {
+            InnerMessage::StringData(data) => write!(
+                f,
+                "{} for {}",
+                data.meta.model.to_uppercase(),
+                data.meta.site.description()
+            ),
+            InnerMessage::BufkitDataError(err) => write!(f, "{}", err),
+        }
+    }
 }
 
 impl From<InnerMessage> for Message {
@@ -17,5 +42,81 @@ impl From<InnerMessage> for Message {
 
 pub(crate) enum InnerMessage {
     StringData(StringData),
-    BufkitDataError(BufkitDataErr),
+    BufkitDataError(ContextualError),
+}
+
+impl InnerMessage {
+    /// Create an error message with information about what was being loaded.
+    pub(crate) fn error(
+        site: &str,
+        model: &str,
+        time: Option<NaiveDateTime>,
+        cause: BufkitDataErr,
+    ) -> Self {
+        InnerMessage::BufkitDataError(ContextualError {
+            site: site.to_owned(),
+            model: model.to_owned(),
+            time,
+            cause,
+        })
+    }
+}
+
+/// An error loading data along with the site, model, and time that was being loaded.
+#[derive(Debug)]
+pub struct ContextualError {
+    site: String,
+    model: String,
+    time: Option<NaiveDateTime>,
+    cause: BufkitDataErr,
+}
+
+impl ContextualError {
+    /// The site that was being loaded.
+    pub fn site(&self) -> &str {
+        &self.site
+    }
+
+    /// The model that was being loaded.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// The time that was being loaded, if the error is about a specific time.
+    pub fn time(&self) -> Option<NaiveDateTime> {
+        self.time
+    }
+
+    /// The error from the archive.
+    pub fn cause(&self) -> &BufkitDataErr {
+        &self.cause
+    }
+
+    /// Discard the context and get the error from the archive.
+    pub fn into_cause(self) -> BufkitDataErr {
+        self.cause
+    }
+}
+
+impl Display for ContextualError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Error loading {} for {}",
+            self.model,
+            self.site.to_uppercase()
+        )?;
+
+        if let Some(time) = self.time {
+            write!(f, " at {}", time.format("%Y-%m-%dT%HZ"))?;
+        }
+
+        write!(f, ": {}", self.cause)
+    }
+}
+
+impl Error for ContextualError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.cause)
+    }
 }
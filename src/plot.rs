@@ -1,20 +1,30 @@
 //! Functions used for plotting data and producing output.
 use crate::{
+    climo::{Climatology, ClimoSource},
+    constants::GP_DATE_FORMAT,
+    error::FwxError,
     messages::{InnerMessage, Message},
     timeseries::{EnsembleSeries, MergedSeries, MetaData},
-    types::{parse_sounding, AnalyzedData},
+    types::{parse_sounding, AnalysisConfig, AnalyzedData, SpreadStats, TimeStepStats},
+    util::site_label,
+    validation::validate,
 };
 use bufcli::{ClimoElement, ClimoQueryInterface, Percentile};
-use crossbeam::{crossbeam_channel::unbounded, scope};
 use metfor::Quantity;
 use rayon::iter::{IterBridge, ParallelBridge, ParallelIterator};
 use std::{
-    error::Error,
     fs::File,
-    io::Write,
+    io::{self, Write},
     path::PathBuf,
     process::{ChildStdin, Command, Stdio},
 };
+use strum::IntoEnumIterator;
+
+/// A pure Rust SVG backend, an alternative to gnuplot.
+#[cfg(feature = "svg-backend")]
+mod svg;
+#[cfg(feature = "svg-backend")]
+pub use svg::{plot_all_svg, save_all_svg};
 
 /// Given an iterator over `StringData` loaded from Bufkit files, filter out any failed results
 /// and make all the plots.
@@ -22,100 +32,453 @@ use std::{
 /// # Arguments
 /// iter - an iterator over ensembles of model runs, make the plot and save it for each ensemble.
 /// prefix - The path to the folder where you want the plots saved.
-pub fn plot_all<I>(iter: I, prefix: &str, mut climo: Option<ClimoQueryInterface>)
+/// climo - The interface to the climatology database, if available.
+/// config - Options for the analysis, if `None` the defaults are used.
+/// names - The template for the output file names, if `None` the defaults are used.
+/// on_error - Called with a description of each error, if `None` the errors are printed, or
+///            emitted as `tracing` events with the `tracing` feature.
+pub fn plot_all<I>(
+    iter: I,
+    prefix: &str,
+    climo: Option<ClimoQueryInterface>,
+    config: Option<AnalysisConfig>,
+    names: Option<FileNameTemplate>,
+    on_error: Option<Box<ErrorHandler>>,
+) -> Result<(), FwxError>
 where
     I: Iterator<Item = Message> + ParallelBridge + Send,
     IterBridge<I>: ParallelIterator<Item = Message> + Send,
 {
-    let (plot_sender, plot_receiver) = unbounded();
-
-    scope(|s| {
-        s.spawn(move |_| {
-            iter.par_bridge()
-                .filter_map(|msg| match msg.payload() {
-                    InnerMessage::StringData(ens_list_strings) => {
-                        let start = ens_list_strings.meta.start;
-                        let end = ens_list_strings.meta.end;
-                        let ens_ser_anal = ens_list_strings
-                            .filter_map(|str_data| parse_sounding(str_data, start, end));
-
-                        if ens_ser_anal.is_empty() {
-                            None
-                        } else {
-                            Some(ens_ser_anal)
-                        }
-                    }
-                    InnerMessage::BufkitDataError(err) => {
-                        println!("Error: {:?}", err);
-                        None
-                    }
-                })
-                .map(|ens_ser_anal| ens_ser_anal.filter_map_inner(AnalyzedData::analyze))
-                .for_each(|analyzed_data| plot_sender.send(analyzed_data).unwrap());
-        });
+    let config = config.unwrap_or_default();
+    let names = names.unwrap_or_default();
+    let on_error = on_error.as_deref();
+    let mut climo = climo.map(Climatology::new);
+    let gp_in = &mut launch_gnuplot(prefix)?;
+
+    // Parse and analyze in parallel.
+    let analyzed: Vec<EnsembleSeries<AnalyzedData>> = iter
+        .par_bridge()
+        .filter_map(|msg| analyze_message(msg, &config, on_error))
+        .collect();
+
+    // Communication with gnuplot is serial.
+    for analyzed_data in analyzed {
+        let meta = &analyzed_data.meta;
+        gp_plot_ens(gp_in, &analyzed_data, &names, GP_PLOT_ENS)
+            .unwrap_or_else(|err| report_error(on_error, meta, err));
+        let stats = analyzed_data.statistics();
+        let merged = analyzed_data.merge();
+        gp_plot_mrg(
+            gp_in,
+            &merged,
+            &stats,
+            climo.as_mut(),
+            prefix,
+            &names,
+            GP_PLOT_MRG,
+            config.all_climo_elements,
+        )
+        .unwrap_or_else(|err| report_error(on_error, &merged.meta, err));
+    }
+
+    Ok(())
+}
+
+/// Plot the merged data for several sites side by side with shared time axes, e.g. to compare a
+/// valley and a ridge site.
+///
+/// Up to three sites are plotted, any more are ignored. The output file is named after the first
+/// site with a `_multi.png` suffix.
+///
+/// # Arguments
+/// sites - the merged data for each site.
+/// prefix - The path to the folder where you want the plot saved.
+/// names - The template for the output file name, if `None` the defaults are used.
+pub fn plot_multi_site(
+    sites: &[&MergedSeries<AnalyzedData>],
+    prefix: &str,
+    names: Option<FileNameTemplate>,
+) -> Result<(), FwxError> {
+    if sites.is_empty() {
+        return Ok(());
+    }
+
+    let names = names.unwrap_or_default();
+    let gp_in = &mut launch_gnuplot(prefix)?;
+
+    gp_plot_multi_site(gp_in, sites, &names)
+}
+
+/// Plot the ensemble and merged data with gnuplot scripts provided at runtime instead of the
+/// built in ones, e.g. to experiment with plot styles.
+///
+/// The scripts are given the same variables and inline data as the built in scripts, see the
+/// comments at the top of `ens_template.plt` and `mrg_template.plt`, and the merged script is
+/// also given the climatology for every `ClimoElement`. They are checked for the inline data they
+/// must use before launching gnuplot, the ensemble script must use `$data` and the merged script
+/// must use `$data` and `$hdw_climo`.
+///
+/// # Arguments
+/// prefix - The path to the folder where you want the plots saved.
+/// ens - The analyzed ensemble to plot.
+/// climo - The interface to the climatology database, if available.
+/// names - The template for the output file names, if `None` the defaults are used.
+/// ens_template - The gnuplot script for the ensemble plot.
+/// mrg_template - The gnuplot script for the merged plot.
+pub fn plot_with_templates(
+    prefix: &str,
+    ens: EnsembleSeries<AnalyzedData>,
+    climo: Option<&mut ClimoQueryInterface>,
+    names: Option<FileNameTemplate>,
+    ens_template: &str,
+    mrg_template: &str,
+) -> Result<(), FwxError> {
+    validate_template("ensemble", ens_template, &["$data"])?;
+    validate_template("merged", mrg_template, &["$data", "$hdw_climo"])?;
+
+    let names = names.unwrap_or_default();
+    let gp_in = &mut launch_gnuplot(prefix)?;
 
-        let gp_in = &mut launch_gnuplot(prefix).unwrap();
-        for analyzed_data in plot_receiver {
-            gp_plot_ens(gp_in, &analyzed_data).unwrap_or_else(|err| println!("{:?}", err));
-            let merged = analyzed_data.merge();
-            gp_plot_mrg(gp_in, &merged, climo.as_mut()).unwrap_or_else(|err| println!("{:?}", err));
+    gp_plot_ens(gp_in, &ens, &names, ens_template)?;
+    let stats = ens.statistics();
+    let merged = ens.merge();
+    gp_plot_mrg(
+        gp_in,
+        &merged,
+        &stats,
+        climo,
+        prefix,
+        &names,
+        mrg_template,
+        true,
+    )
+}
+
+/// Check that a gnuplot script uses all the `required` inline data blocks.
+fn validate_template(
+    name: &'static str,
+    template: &str,
+    required: &[&'static str],
+) -> Result<(), FwxError> {
+    match required.iter().find(|&&block| !template.contains(block)) {
+        Some(&missing) => Err(FwxError::InvalidTemplate { name, missing }),
+        None => Ok(()),
+    }
+}
+
+/// A function for reporting errors from the plot and save functions, like `plot_all` and
+/// `save_all`, it is called with a description of the error including the site and model if they
+/// are known. Validation warnings are reported with it too when `AnalysisConfig::validate` is set.
+pub type ErrorHandler = dyn Fn(&str) + Send + Sync;
+
+/// The file formats available for saving data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Whitespace separated text files suitable for gnuplot.
+    #[default]
+    GnuplotDat,
+    /// RFC 4180 CSV files with a header row and missing values left empty. The delimiter may be
+    /// set to something other than a comma, e.g. a tab for TSV files.
+    Csv { delimiter: char },
+    /// JSON files, with missing values as `null`.
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+/// A template for the names of the output files.
+///
+/// The file names are the prefix, the station number, the model, the date if included, and a
+/// suffix describing the contents of the file, e.g. `727730_GFS_ens.png` with the defaults.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileNameTemplate {
+    /// Added to the start of every file name.
+    pub prefix: String,
+    /// Include the time considered now in the `MetaData` in the file name.
+    pub include_date: bool,
+    /// The `chrono` format string for the date.
+    pub date_format: String,
+}
+
+impl Default for FileNameTemplate {
+    fn default() -> Self {
+        FileNameTemplate {
+            prefix: String::new(),
+            include_date: false,
+            date_format: "%Y%m%d%H".to_owned(),
         }
-    })
-    .unwrap();
+    }
+}
+
+impl FileNameTemplate {
+    /// Create the file name for the data described by `meta`, the `suffix` is added to the end
+    /// and should include any extension.
+    pub fn render(&self, meta: &MetaData, suffix: &str) -> String {
+        let mut name = format!(
+            "{}{}_{}",
+            self.prefix,
+            meta.site.station_num,
+            meta.model.to_uppercase()
+        );
+
+        if self.include_date {
+            name.push('_');
+            name.push_str(&meta.now.format(&self.date_format).to_string());
+        }
+
+        name.push_str(suffix);
+        name
+    }
 }
 
 /// Given an iterator over `StringData` loaded from Bufkit files, filter out any failed results
-/// and save the data in files suitable for gnuplot.
+/// and save the data in files.
 ///
 /// # Arguments
 /// iter - an iterator over ensembles of model runs, make the plot and save it for each ensemble.
 /// prefix - The path to the folder where you want the plots saved.
+/// climo - The interface to the climatology database, if available.
+/// config - Options for the analysis, if `None` the defaults are used.
+/// format - The file format to save the data in. Climatology data is only saved with the
+///          `GnuplotDat` format.
+/// names - The template for the output file names, if `None` the defaults are used.
+/// on_error - Called with a description of each error, if `None` the errors are printed, or
+///            emitted as `tracing` events with the `tracing` feature.
 pub fn save_all(
     iter: impl Iterator<Item = Message>,
     prefix: &str,
-    mut climo: Option<ClimoQueryInterface>,
-) -> Result<(), Box<dyn Error>> {
-    use InnerMessage::*;
+    climo: Option<ClimoQueryInterface>,
+    config: Option<AnalysisConfig>,
+    format: OutputFormat,
+    names: Option<FileNameTemplate>,
+    on_error: Option<Box<ErrorHandler>>,
+) -> Result<(), FwxError> {
+    let config = config.unwrap_or_default();
+    let names = names.unwrap_or_default();
+    let on_error = on_error.as_deref();
+    let mut climo = climo.map(Climatology::new);
+
+    iter.filter_map(|msg| analyze_message(msg, &config, on_error))
+        .for_each(|analyzed_data| {
+            let meta = analyzed_data.meta.clone();
+
+            match format {
+                OutputFormat::GnuplotDat => gp_save(prefix, analyzed_data, climo.as_mut(), &names),
+                OutputFormat::Csv { delimiter } => {
+                    csv_save(prefix, analyzed_data, delimiter, &names)
+                }
+                #[cfg(feature = "serde")]
+                OutputFormat::Json => json_save(prefix, analyzed_data, &names),
+            }
+            .unwrap_or_else(|err| report_error(on_error, &meta, err))
+        });
+
+    Ok(())
+}
 
-    iter.filter_map(|msg| match msg.payload() {
-        StringData(ens_list_strings) => {
+/// Given an iterator over `StringData` loaded from Bufkit files, filter out any failed results
+/// and save the data in gnuplot readable files, processing each ensemble in parallel.
+///
+/// The `ClimoQueryInterface` cannot be shared between threads, so the climatology files are
+/// written sequentially after everything else is saved. The default `AnalysisConfig` and
+/// `FileNameTemplate` are used.
+///
+/// # Arguments
+/// iter - an iterator over ensembles of model runs, save the data for each ensemble.
+/// prefix - The path to the folder where you want the files saved.
+/// climo - The interface to the climatology database, if available.
+/// on_error - Called with a description of each error, if `None` the errors are printed, or
+///            emitted as `tracing` events with the `tracing` feature.
+pub fn save_all_parallel(
+    iter: impl Iterator<Item = Message> + Send,
+    prefix: &str,
+    climo: Option<ClimoQueryInterface>,
+    on_error: Option<Box<ErrorHandler>>,
+) -> Result<(), FwxError> {
+    let config = AnalysisConfig::default();
+    let names = FileNameTemplate::default();
+    let on_error = on_error.as_deref();
+    let mut climo = climo.map(Climatology::new);
+
+    let saved: Vec<MetaData> = iter
+        .par_bridge()
+        .filter_map(|msg| analyze_message(msg, &config, on_error))
+        .filter_map(|analyzed_data| {
+            let meta = analyzed_data.meta.clone();
+            gp_save_data(prefix, analyzed_data, &names)
+                .map_err(|err| report_error(on_error, &meta, err))
+                .ok()
+        })
+        .collect();
+
+    for meta in saved {
+        gp_save_climo(prefix, &meta, climo.as_mut(), &names)
+            .unwrap_or_else(|err| report_error(on_error, &meta, err));
+    }
+
+    Ok(())
+}
+
+/// Report an error along with the site and model it happened for.
+///
+/// The description is passed to the handler if there is one, otherwise it is reported with
+/// `trace_error!`.
+fn report_error(on_error: Option<&ErrorHandler>, meta: &MetaData, err: FwxError) {
+    let description = format!(
+        "Error for {} at {}: {}",
+        meta.model.to_uppercase(),
+        meta.site.description(),
+        err
+    );
+
+    match on_error {
+        Some(on_error) => on_error(&description),
+        None => {
+            trace_error!("{}", description);
+        }
+    }
+}
+
+/// Parse and analyze the data in a message, reporting any errors.
+///
+/// The errors are passed to `on_error` if there is a handler.
+fn analyze_message(
+    msg: Message,
+    config: &AnalysisConfig,
+    on_error: Option<&ErrorHandler>,
+) -> Option<EnsembleSeries<AnalyzedData>> {
+    match msg.payload() {
+        InnerMessage::StringData(ens_list_strings) => {
             let start = ens_list_strings.meta.start;
             let end = ens_list_strings.meta.end;
-            let ens_ser_anal =
-                ens_list_strings.filter_map(|str_data| parse_sounding(str_data, start, end));
+            let mut ens_ser_anal = ens_list_strings
+                .filter_map(|str_data| parse_sounding(str_data, start, end))
+                .deduplicate_by_init_time();
+            ens_ser_anal.sort_by_init_time();
 
             if ens_ser_anal.is_empty() {
+                trace_warn!(
+                    "no soundings parsed for {} at {}",
+                    ens_list_strings.meta.model,
+                    ens_list_strings.meta.site.station_num
+                );
                 None
             } else {
-                Some(ens_ser_anal)
+                let analyzed = ens_ser_anal
+                    .filter_map_inner(|snd| AnalyzedData::analyze_with_config(snd, config));
+
+                if config.validate {
+                    report_validation_warnings(&analyzed, on_error);
+                }
+
+                Some(analyzed)
             }
         }
-        BufkitDataError(err) => {
-            println!("Error: {:?}", err);
+        InnerMessage::BufkitDataError(err) => {
+            match on_error {
+                Some(on_error) => on_error(&err.to_string()),
+                None => {
+                    trace_error!("{}", err);
+                }
+            }
             None
         }
-    })
-    .map(|ens_ser_anal| ens_ser_anal.filter_map_inner(AnalyzedData::analyze))
-    .for_each(|analyzed_data| gp_save(prefix, analyzed_data, climo.as_mut()).unwrap_or(()));
+    }
+}
 
-    Ok(())
+/// Validate the data and report any warnings along with the site and model.
+///
+/// The warnings are passed to the handler if there is one, otherwise they are printed to stderr.
+fn report_validation_warnings(ens: &EnsembleSeries<AnalyzedData>, on_error: Option<&ErrorHandler>) {
+    let report = validate(ens);
+
+    for warning in report.warnings {
+        let description = format!(
+            "Warning for {} at {}: {}",
+            ens.meta.model.to_uppercase(),
+            ens.meta.site.description(),
+            warning
+        );
+
+        match on_error {
+            Some(on_error) => on_error(&description),
+            None => eprintln!("{}", description),
+        }
+    }
 }
 
 const GP_INIT: &str = include_str!("plot/initialize.plt");
 const GP_PLOT_ENS: &str = include_str!("plot/ens_template.plt");
 const GP_PLOT_MRG: &str = include_str!("plot/mrg_template.plt");
-const GP_DATE_FORMAT: &str = "%Y-%m-%d-%H";
+const GP_PLOT_MULTI_SITE: &str = include_str!("plot/multi_site_template.plt");
+const CSV_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// The oldest gnuplot version, (major, minor), that supports everything used in the templates,
+/// like the inline data blocks.
+pub const MIN_GNUPLOT_VERSION: (u32, u32) = (5, 0);
+
+/// Check if the gnuplot executable is in the PATH, without launching it.
+pub fn is_gnuplot_available() -> bool {
+    let exe = format!("gnuplot{}", std::env::consts::EXE_SUFFIX);
+
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(&exe).is_file()))
+        .unwrap_or(false)
+}
+
+/// Get the (major, minor) version of the gnuplot in the PATH from `gnuplot --version`.
+pub fn check_gnuplot_version() -> Result<(u32, u32), FwxError> {
+    let output = Command::new("gnuplot")
+        .arg("--version")
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => FwxError::GnuplotNotFound,
+            _ => FwxError::GnuplotLaunch(err),
+        })?;
+
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    // The output looks like "gnuplot 5.4 patchlevel 3"
+    let version = text
+        .split_whitespace()
+        .nth(1)
+        .and_then(|version| {
+            let mut parts = version.split('.');
+            let major = parts.next()?.parse().ok()?;
+            let minor = parts.next()?.parse().ok()?;
+            Some((major, minor))
+        })
+        .ok_or_else(|| {
+            FwxError::GnuplotLaunch(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unrecognized gnuplot version: {}", text.trim()),
+            ))
+        })?;
+
+    Ok(version)
+}
 
 /// Create a pipe to a gnuplot process and set up the terminal, etc
 ///
 /// output_prefix is a path to a folder to put the images in when completed.
-fn launch_gnuplot(output_prefix: &str) -> Result<ChildStdin, Box<dyn Error>> {
+fn launch_gnuplot(output_prefix: &str) -> Result<ChildStdin, FwxError> {
+    let found = check_gnuplot_version()?;
+    if found < MIN_GNUPLOT_VERSION {
+        return Err(FwxError::GnuplotVersionTooOld {
+            found,
+            required: MIN_GNUPLOT_VERSION,
+        });
+    }
+
     let gp = Command::new("gnuplot")
         .arg("-p")
         .stdin(Stdio::piped())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
-        .spawn()?;
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => FwxError::GnuplotNotFound,
+            _ => FwxError::GnuplotLaunch(err),
+        })?;
 
     let mut gp_in = gp.stdin.expect("no stdin assigned, should be impossible!");
     gp_in.write_all(GP_INIT.as_bytes())?;
@@ -124,47 +487,69 @@ fn launch_gnuplot(output_prefix: &str) -> Result<ChildStdin, Box<dyn Error>> {
     Ok(gp_in)
 }
 
-/// Plot a merged time series, including a heat map.
-fn gp_plot_mrg(
+/// Set the variables the gnuplot scripts use for ranges, titles, etc. for a plot.
+fn gp_write_header_vars(
     gp: &mut ChildStdin,
-    mg: &MergedSeries<AnalyzedData>,
-    mut climo: Option<&mut ClimoQueryInterface>,
-) -> Result<(), Box<dyn Error>> {
-    let MergedSeries::<AnalyzedData> { meta: meta_mg, .. } = &mg;
-
-    // Set variables for the gnuplot script to use for ranges, etc
-    writeln!(gp, "num_hours={}", (meta_mg.end - meta_mg.now).num_hours())?;
-    writeln!(gp, "now_time=\"{}\"", meta_mg.now.format(GP_DATE_FORMAT),)?;
-    writeln!(
-        gp,
-        "start_time=\"{}\"",
-        meta_mg.start.format(GP_DATE_FORMAT)
-    )?;
-    writeln!(gp, "end_time=\"{}\"", meta_mg.end.format(GP_DATE_FORMAT))?;
+    meta: &MetaData,
+    output_name: &str,
+) -> Result<(), io::Error> {
+    writeln!(gp, "num_hours={}", (meta.end - meta.now).num_hours())?;
+    writeln!(gp, "now_time=\"{}\"", meta.now.format(GP_DATE_FORMAT))?;
+    writeln!(gp, "start_time=\"{}\"", meta.start.format(GP_DATE_FORMAT))?;
+    writeln!(gp, "end_time=\"{}\"", meta.end.format(GP_DATE_FORMAT))?;
     writeln!(
         gp,
         "main_title=\"Fire Weather Parameters - {} - {}\"",
-        meta_mg.site.description(),
-        meta_mg.model.to_uppercase()
-    )?;
-    writeln!(
-        gp,
-        "output_name=\"{}_{}\"",
-        meta_mg.site.station_num,
-        meta_mg.model.to_uppercase()
+        site_label(meta),
+        meta.model.to_uppercase()
     )?;
+    writeln!(gp, "output_name=\"{}\"", output_name)?;
+
+    Ok(())
+}
+
+/// Plot a merged time series, including a heat map.
+///
+/// The climatology for every `ClimoElement` is only written if `all_climo_elements` is set, the
+/// HDW climatology is always written.
+#[allow(clippy::too_many_arguments)]
+fn gp_plot_mrg<C: ClimoSource>(
+    gp: &mut ChildStdin,
+    mg: &MergedSeries<AnalyzedData>,
+    stats: &[TimeStepStats],
+    mut climo: Option<&mut C>,
+    prefix: &str,
+    names: &FileNameTemplate,
+    template: &str,
+    all_climo_elements: bool,
+) -> Result<(), FwxError> {
+    let MergedSeries::<AnalyzedData> { meta: meta_mg, .. } = &mg;
+
+    // Write a text summary to go along with the plot.
+    let summary_path = PathBuf::from(&format!("{}/{}", prefix, names.render(meta_mg, "_mrg.txt")));
+    write_summary_stats(mg, &mut File::create(summary_path)?)?;
+
+    gp_write_header_vars(gp, meta_mg, &names.render(meta_mg, "_mrg.png"))?;
 
     writeln!(gp, "$data << EOD")?;
     write_merged_data(mg, gp)?;
     writeln!(gp, "EOD")?;
 
+    // Write out the ensemble spread
+    writeln!(gp, "$stats << EOD")?;
+    write_ensemble_stats(meta_mg, stats, gp)?;
+    writeln!(gp, "EOD")?;
+
     // Try to get the climate data for the HDW and add that to the data
     writeln!(gp, "$hdw_climo << EOD")?;
     write_climo(&meta_mg, ClimoElement::HDW, gp, &mut climo)?;
     writeln!(gp, "EOD")?;
+    if all_climo_elements {
+        write_all_climo_elements(meta_mg, gp, &mut climo)?;
+    }
 
     // Draw the graph
-    gp.write_all(GP_PLOT_MRG.as_bytes())?;
+    gp.write_all(template.as_bytes())?;
 
     Ok(())
 }
@@ -173,26 +558,12 @@ fn gp_plot_mrg(
 fn gp_plot_ens(
     gp: &mut ChildStdin,
     ens: &EnsembleSeries<AnalyzedData>,
-) -> Result<(), Box<dyn Error>> {
+    names: &FileNameTemplate,
+    template: &str,
+) -> Result<(), FwxError> {
     let EnsembleSeries::<AnalyzedData> { meta, .. } = ens;
 
-    // Set variables for the gnuplot script to use for ranges, etc
-    writeln!(gp, "num_hours={}", (meta.end - meta.now).num_hours())?;
-    writeln!(gp, "now_time=\"{}\"", meta.now.format(GP_DATE_FORMAT),)?;
-    writeln!(gp, "start_time=\"{}\"", meta.start.format(GP_DATE_FORMAT))?;
-    writeln!(gp, "end_time=\"{}\"", meta.end.format(GP_DATE_FORMAT))?;
-    writeln!(
-        gp,
-        "main_title=\"Fire Weather Parameters - {} - {}\"",
-        meta.site.name.as_ref().unwrap_or(&meta.site.description()),
-        meta.model.to_uppercase()
-    )?;
-    writeln!(
-        gp,
-        "output_name=\"{}_{}_ens.png\"",
-        meta.site.station_num,
-        meta.model.to_uppercase()
-    )?;
+    gp_write_header_vars(gp, meta, &names.render(meta, "_ens.png"))?;
 
     // Write out the ensemble data
     writeln!(gp, "$data << EOD")?;
@@ -200,66 +571,314 @@ fn gp_plot_ens(
     writeln!(gp, "EOD")?;
 
     // Draw the graph
-    gp.write_all(GP_PLOT_ENS.as_bytes())?;
+    gp.write_all(template.as_bytes())?;
+
+    Ok(())
+}
+
+/// The most sites `gp_plot_multi_site` will put in a single plot.
+const MAX_MULTI_SITES: usize = 3;
+
+/// Plot several merged time series side by side, one column for each site.
+fn gp_plot_multi_site(
+    gp: &mut ChildStdin,
+    sites: &[&MergedSeries<AnalyzedData>],
+    names: &FileNameTemplate,
+) -> Result<(), FwxError> {
+    let sites = &sites[..sites.len().min(MAX_MULTI_SITES)];
+    let first = match sites.first() {
+        Some(first) => &first.meta,
+        None => return Ok(()),
+    };
+
+    // Share the time axis between all the sites.
+    let start = sites
+        .iter()
+        .map(|mg| mg.meta.start)
+        .min()
+        .unwrap_or(first.start);
+    let end = sites
+        .iter()
+        .map(|mg| mg.meta.end)
+        .max()
+        .unwrap_or(first.end);
+
+    writeln!(gp, "num_sites={}", sites.len())?;
+    writeln!(gp, "start_time=\"{}\"", start.format(GP_DATE_FORMAT))?;
+    writeln!(gp, "end_time=\"{}\"", end.format(GP_DATE_FORMAT))?;
+    writeln!(
+        gp,
+        "main_title=\"Fire Weather Parameters - {}\"",
+        first.model.to_uppercase()
+    )?;
+    writeln!(gp, "output_name=\"{}\"", names.render(first, "_multi.png"))?;
+
+    for (i, mg) in sites.iter().enumerate() {
+        writeln!(
+            gp,
+            "site_title_{}=\"{} - {}\"",
+            i + 1,
+            site_label(&mg.meta),
+            mg.meta.model.to_uppercase()
+        )?;
+
+        writeln!(gp, "$data_{} << EOD", i + 1)?;
+        write_merged_data(mg, gp)?;
+        writeln!(gp, "EOD")?;
+    }
+
+    // Draw the graph
+    gp.write_all(GP_PLOT_MULTI_SITE.as_bytes())?;
 
     Ok(())
 }
 
 /// Save a set of data
-fn gp_save(
+fn gp_save<C: ClimoSource>(
+    prefix: &str,
+    ens: EnsembleSeries<AnalyzedData>,
+    climo: Option<&mut C>,
+    names: &FileNameTemplate,
+) -> Result<(), FwxError> {
+    let meta = gp_save_data(prefix, ens, names)?;
+    gp_save_climo(prefix, &meta, climo, names)
+}
+
+/// Save the ensemble, statistics, and merged data files, returning the `MetaData` of the merged
+/// data so the climatology can be saved too.
+fn gp_save_data(
+    prefix: &str,
+    ens: EnsembleSeries<AnalyzedData>,
+    names: &FileNameTemplate,
+) -> Result<MetaData, FwxError> {
+    let EnsembleSeries::<AnalyzedData> { meta, .. } = &ens;
+
+    // Build the file names to save the data to
+    let fname_ens: PathBuf =
+        PathBuf::from(&format!("{}/{}", prefix, names.render(meta, "_ens.dat")));
+    let f_ens = &mut File::create(&fname_ens)?;
+    let fname_mrg: PathBuf =
+        PathBuf::from(&format!("{}/{}", prefix, names.render(meta, "_mrg.dat")));
+    let f_mrg = &mut File::create(&fname_mrg)?;
+
+    let fname_sts: PathBuf =
+        PathBuf::from(&format!("{}/{}", prefix, names.render(meta, "_sts.dat")));
+    let f_sts = &mut File::create(&fname_sts)?;
+
+    write_ensemble_data(&ens, f_ens)?;
+    write_ensemble_stats(meta, &ens.statistics(), f_sts)?;
+
+    // Make a merged data and write that out too.
+    let merged = ens.merge();
+
+    write_merged_data(&merged, f_mrg)?;
+
+    Ok(merged.meta)
+}
+
+/// Save the climatology file for a set of data.
+fn gp_save_climo<C: ClimoSource>(
+    prefix: &str,
+    meta: &MetaData,
+    mut climo: Option<&mut C>,
+    names: &FileNameTemplate,
+) -> Result<(), FwxError> {
+    let fname_cli: PathBuf =
+        PathBuf::from(&format!("{}/{}", prefix, names.render(meta, "_cli.dat")));
+    let f_cli = &mut File::create(&fname_cli)?;
+
+    write_climo(meta, ClimoElement::HDW, f_cli, &mut climo)
+}
+
+/// Save a set of data as CSV
+fn csv_save(
     prefix: &str,
     ens: EnsembleSeries<AnalyzedData>,
-    mut climo: Option<&mut ClimoQueryInterface>,
-) -> Result<(), Box<dyn Error>> {
+    delimiter: char,
+    names: &FileNameTemplate,
+) -> Result<(), FwxError> {
     let EnsembleSeries::<AnalyzedData> { meta, .. } = &ens;
 
+    let extension = if delimiter == '\t' { "tsv" } else { "csv" };
+
     // Build the file names to save the data to
     let fname_ens: PathBuf = PathBuf::from(&format!(
-        "{}/{}_{}_ens.dat",
+        "{}/{}",
         prefix,
-        meta.site.station_num,
-        meta.model.to_uppercase()
+        names.render(meta, &format!("_ens.{}", extension))
     ));
-    let f_ens = &mut File::create(&fname_ens)?;
+    let f_ens = &mut std::io::BufWriter::new(File::create(&fname_ens)?);
     let fname_mrg: PathBuf = PathBuf::from(&format!(
-        "{}/{}_{}_mrg.dat",
+        "{}/{}",
         prefix,
-        meta.site.station_num,
-        meta.model.to_uppercase()
+        names.render(meta, &format!("_mrg.{}", extension))
     ));
-    let f_mrg = &mut File::create(&fname_mrg)?;
+    let f_mrg = &mut std::io::BufWriter::new(File::create(&fname_mrg)?);
 
-    let fname_cli: PathBuf = PathBuf::from(&format!(
-        "{}/{}_{}_cli.dat",
-        prefix,
-        meta.site.station_num,
-        meta.model.to_uppercase()
-    ));
-    let f_cli = &mut File::create(&fname_cli)?;
-
-    write_ensemble_data(&ens, f_ens)?;
+    write_ensemble_csv(&ens, delimiter, f_ens)?;
 
     // Make a merged data and write that out too.
     let merged = ens.merge();
 
-    write_merged_data(&merged, f_mrg)?;
+    write_merged_csv(&merged, delimiter, f_mrg)?;
 
-    write_climo(&merged.meta, ClimoElement::HDW, f_cli, &mut climo)?;
+    Ok(())
+}
+
+/// The CSV header row for the `AnalyzedData` fields.
+const CSV_HEADER: &[&str] = &[
+    "valid_time",
+    "lead_time",
+    "blow_up_dt",
+    "blow_up_height",
+    "hdw",
+    "ffwi",
+    "haines",
+    "mixing_height",
+    "surface_rh",
+    "surface_temperature",
+    "surface_wind_speed",
+    "surface_wind_direction",
+    "transport_wind_speed",
+    "transport_wind_direction",
+];
+
+/// Write the ensemble data as CSV with the init_time in the first column.
+fn write_ensemble_csv<W: Write>(
+    ens: &EnsembleSeries<AnalyzedData>,
+    delimiter: char,
+    dest: &mut W,
+) -> Result<(), FwxError> {
+    let delimiter = delimiter.to_string();
+
+    let mut header = vec!["init_time"];
+    header.extend_from_slice(CSV_HEADER);
+    write!(dest, "{}\r\n", header.join(&delimiter))?;
+
+    for (init_time, time_series) in ens.data.iter() {
+        let init_time = init_time.format(CSV_DATE_FORMAT).to_string();
+        for anal in time_series.as_ref().iter() {
+            let mut fields = vec![init_time.clone()];
+            fields.extend(csv_fields(anal));
+            write!(dest, "{}\r\n", fields.join(&delimiter))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the merged time series data as CSV.
+fn write_merged_csv<W: Write>(
+    mrg: &MergedSeries<AnalyzedData>,
+    delimiter: char,
+    dest: &mut W,
+) -> Result<(), FwxError> {
+    let delimiter = delimiter.to_string();
+
+    write!(dest, "{}\r\n", CSV_HEADER.join(&delimiter))?;
+
+    for anal in mrg.data.as_ref().iter() {
+        write!(dest, "{}\r\n", csv_fields(anal).join(&delimiter))?;
+    }
+
+    Ok(())
+}
+
+/// Format the fields of an `AnalyzedData` for CSV, leaving missing values empty.
+fn csv_fields(anal: &AnalyzedData) -> Vec<String> {
+    let csv_float = |val: f64| {
+        if val.is_nan() {
+            String::new()
+        } else {
+            val.to_string()
+        }
+    };
+
+    let AnalyzedData {
+        valid_time,
+        lead_time,
+        hdw,
+        ffwi,
+        blow_up_dt,
+        blow_up_height,
+        haines_index,
+        mixing_height,
+        surface_rh,
+        surface_temperature_c,
+        surface_wind_speed_ms,
+        surface_wind_direction_deg,
+        transport_wind_speed_ms,
+        transport_wind_dir_deg,
+    } = anal;
+
+    vec![
+        valid_time.format(CSV_DATE_FORMAT).to_string(),
+        lead_time.to_string(),
+        csv_float(blow_up_dt.unpack()),
+        csv_float(blow_up_height.unpack()),
+        csv_float(*hdw),
+        csv_float(*ffwi),
+        if *haines_index == 0 {
+            String::new()
+        } else {
+            haines_index.to_string()
+        },
+        csv_float(mixing_height.unpack()),
+        csv_float(*surface_rh),
+        csv_float(*surface_temperature_c),
+        csv_float(*surface_wind_speed_ms),
+        csv_float(*surface_wind_direction_deg),
+        csv_float(*transport_wind_speed_ms),
+        csv_float(*transport_wind_dir_deg),
+    ]
+}
+
+/// Save a set of data as JSON
+#[cfg(feature = "serde")]
+fn json_save(
+    prefix: &str,
+    ens: EnsembleSeries<AnalyzedData>,
+    names: &FileNameTemplate,
+) -> Result<(), FwxError> {
+    let EnsembleSeries::<AnalyzedData> { meta, .. } = &ens;
+
+    // Build the file names to save the data to
+    let fname_ens: PathBuf =
+        PathBuf::from(&format!("{}/{}", prefix, names.render(meta, "_ens.json")));
+    let f_ens = std::io::BufWriter::new(File::create(&fname_ens)?);
+    let fname_mrg: PathBuf =
+        PathBuf::from(&format!("{}/{}", prefix, names.render(meta, "_mrg.json")));
+    let f_mrg = std::io::BufWriter::new(File::create(&fname_mrg)?);
+
+    serde_json::to_writer(f_ens, &ens).map_err(std::io::Error::from)?;
+
+    // Make a merged data and write that out too.
+    let merged = ens.merge();
+
+    serde_json::to_writer(f_mrg, &merged).map_err(std::io::Error::from)?;
 
     Ok(())
 }
 
 /// Write the ensemble data in a gnuplot readable format.
-fn write_ensemble_data<W: Write>(
+///
+/// Each model run is written as a block starting with a `# init_time:` comment and ending with a
+/// blank line.
+pub fn write_ensemble_data<W: Write>(
     ens: &EnsembleSeries<AnalyzedData>,
     dest: &mut W,
-) -> Result<(), Box<dyn Error>> {
+) -> Result<(), io::Error> {
     let EnsembleSeries { meta, data } = ens;
 
     // Write some comments about the meta data
     write_meta_data_header(&meta, dest)?;
     // Write a header row
-    writeln!(dest, "valid_time lead_time blow_up_dt blow_up_height hdw")?;
+    writeln!(
+        dest,
+        "valid_time lead_time blow_up_dt blow_up_height hdw ffwi haines mixing_height surface_rh \
+         surface_temperature surface_wind_speed surface_wind_direction transport_wind_speed \
+         transport_wind_direction"
+    )?;
     // Write out ensemble members/model runs in block format
     for (init_time, time_series) in data.iter() {
         writeln!(dest, "# init_time: {}", init_time.format(GP_DATE_FORMAT))?;
@@ -267,18 +886,36 @@ fn write_ensemble_data<W: Write>(
             valid_time,
             lead_time,
             hdw,
+            ffwi,
             blow_up_dt,
             blow_up_height,
+            haines_index,
+            mixing_height,
+            surface_rh,
+            surface_temperature_c,
+            surface_wind_speed_ms,
+            surface_wind_direction_deg,
+            transport_wind_speed_ms,
+            transport_wind_dir_deg,
         } in time_series.as_ref().iter()
         {
             writeln!(
                 dest,
-                "{} {} {} {} {}",
+                "{} {} {} {} {} {} {} {} {} {} {} {} {} {}",
                 valid_time.format(GP_DATE_FORMAT),
                 lead_time,
                 blow_up_dt.unpack(),
                 blow_up_height.unpack(),
-                hdw
+                hdw,
+                ffwi,
+                haines_index,
+                mixing_height.unpack(),
+                surface_rh,
+                surface_temperature_c,
+                surface_wind_speed_ms,
+                surface_wind_direction_deg,
+                transport_wind_speed_ms,
+                transport_wind_dir_deg
             )?;
         }
 
@@ -288,48 +925,190 @@ fn write_ensemble_data<W: Write>(
     Ok(())
 }
 
+/// Write the ensemble data as a single table with the initialization time as a column.
+///
+/// This is easier for tools other than gnuplot to read, there are no blocks or comments, only a
+/// single header row.
+pub fn write_ensemble_data_tabular<W: Write>(
+    ens: &EnsembleSeries<AnalyzedData>,
+    dest: &mut W,
+) -> Result<(), io::Error> {
+    writeln!(
+        dest,
+        "init_time valid_time lead_time blow_up_dt blow_up_height hdw ffwi haines mixing_height \
+         surface_rh surface_temperature surface_wind_speed surface_wind_direction \
+         transport_wind_speed transport_wind_direction"
+    )?;
+
+    for (init_time, time_series) in ens.data.iter() {
+        for AnalyzedData {
+            valid_time,
+            lead_time,
+            hdw,
+            ffwi,
+            blow_up_dt,
+            blow_up_height,
+            haines_index,
+            mixing_height,
+            surface_rh,
+            surface_temperature_c,
+            surface_wind_speed_ms,
+            surface_wind_direction_deg,
+            transport_wind_speed_ms,
+            transport_wind_dir_deg,
+        } in time_series.as_ref().iter()
+        {
+            writeln!(
+                dest,
+                "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+                init_time.format(GP_DATE_FORMAT),
+                valid_time.format(GP_DATE_FORMAT),
+                lead_time,
+                blow_up_dt.unpack(),
+                blow_up_height.unpack(),
+                hdw,
+                ffwi,
+                haines_index,
+                mixing_height.unpack(),
+                surface_rh,
+                surface_temperature_c,
+                surface_wind_speed_ms,
+                surface_wind_direction_deg,
+                transport_wind_speed_ms,
+                transport_wind_dir_deg
+            )?;
+        }
+    }
+    Ok(())
+}
+
 /// Write the merged time series data in a gnuplot readable format
 fn write_merged_data<W: Write>(
     mrg: &MergedSeries<AnalyzedData>,
     dest: &mut W,
-) -> Result<(), Box<dyn Error>> {
+) -> Result<(), FwxError> {
     let MergedSeries { meta, data } = mrg;
 
     // Write some comments about the meta data
     write_meta_data_header(&meta, dest)?;
     // Write a header row
-    writeln!(dest, "valid_time lead_time blow_up_dt blow_up_height hdw")?;
+    writeln!(
+        dest,
+        "valid_time lead_time blow_up_dt blow_up_height hdw ffwi haines mixing_height surface_rh \
+         surface_temperature surface_wind_speed surface_wind_direction transport_wind_speed \
+         transport_wind_direction"
+    )?;
     // Write out ensemble members/model runs in block format
 
     for AnalyzedData {
         valid_time,
         lead_time,
         hdw,
+        ffwi,
         blow_up_dt,
         blow_up_height,
+        haines_index,
+        mixing_height,
+        surface_rh,
+        surface_temperature_c,
+        surface_wind_speed_ms,
+        surface_wind_direction_deg,
+        transport_wind_speed_ms,
+        transport_wind_dir_deg,
     } in data.as_ref().iter()
     {
         writeln!(
             dest,
-            "{} {} {} {} {}",
+            "{} {} {} {} {} {} {} {} {} {} {} {} {} {}",
             valid_time.format(GP_DATE_FORMAT),
             lead_time,
             blow_up_dt.unpack(),
             blow_up_height.unpack(),
-            hdw
+            hdw,
+            ffwi,
+            haines_index,
+            mixing_height.unpack(),
+            surface_rh,
+            surface_temperature_c,
+            surface_wind_speed_ms,
+            surface_wind_direction_deg,
+            transport_wind_speed_ms,
+            transport_wind_dir_deg
         )?;
     }
 
     Ok(())
 }
 
+/// Write the ensemble spread statistics in a gnuplot readable format.
+fn write_ensemble_stats<W: Write>(
+    meta: &MetaData,
+    stats: &[TimeStepStats],
+    dest: &mut W,
+) -> Result<(), FwxError> {
+    // Write some comments about the meta data
+    write_meta_data_header(meta, dest)?;
+    // Write a header row
+    writeln!(
+        dest,
+        "valid_time hdw_min hdw_max hdw_mean hdw_std \
+         blow_up_dt_min blow_up_dt_max blow_up_dt_mean blow_up_dt_std \
+         blow_up_height_min blow_up_height_max blow_up_height_mean blow_up_height_std"
+    )?;
+
+    for TimeStepStats {
+        valid_time,
+        hdw,
+        blow_up_dt,
+        blow_up_height,
+    } in stats.iter()
+    {
+        write!(dest, "{}", valid_time.format(GP_DATE_FORMAT))?;
+        for SpreadStats {
+            min,
+            max,
+            mean,
+            std_dev,
+        } in &[hdw, blow_up_dt, blow_up_height]
+        {
+            write!(dest, " {} {} {} {}", min, max, mean, std_dev)?;
+        }
+        writeln!(dest)?;
+    }
+
+    Ok(())
+}
+
+/// Write the climatology for every `ClimoElement` as gnuplot inline data blocks.
+///
+/// Each element is written in its own block named `$climo_` followed by the lowercase name of the
+/// element, e.g. `$climo_hdw`. If the climatology is not available, each block has a single row
+/// of NaN values.
+pub fn write_all_climo_elements<W: Write, C: ClimoSource>(
+    meta: &MetaData,
+    dest: &mut W,
+    climo: &mut Option<&mut C>,
+) -> Result<(), FwxError> {
+    for element in ClimoElement::iter() {
+        writeln!(
+            dest,
+            "$climo_{} << EOD",
+            format!("{:?}", element).to_lowercase()
+        )?;
+        write_climo(meta, element, dest, climo)?;
+        writeln!(dest, "EOD")?;
+    }
+
+    Ok(())
+}
+
 /// Write out the climate data for the HDW
-fn write_climo<W: Write>(
+fn write_climo<W: Write, C: ClimoSource>(
     meta: &MetaData,
     element: ClimoElement,
     dest: &mut W,
-    climo: &mut Option<&mut ClimoQueryInterface>,
-) -> Result<(), Box<dyn Error>> {
+    climo: &mut Option<&mut C>,
+) -> Result<(), FwxError> {
     write_meta_data_header(meta, dest)?;
 
     let MetaData {
@@ -379,16 +1158,89 @@ fn write_climo<W: Write>(
     Ok(())
 }
 
-/// Write a header to a data file/section in gnuplot comment form.
-fn write_meta_data_header<W: Write>(meta: &MetaData, dest: &mut W) -> Result<(), Box<dyn Error>> {
+/// Write a human readable summary of the merged data.
+///
+/// The summary includes the site, model, time window, number of time steps, HDW statistics, and
+/// the maximum blow up height. NaN values are skipped when calculating the statistics.
+pub fn write_summary_stats<W: Write>(
+    merged: &MergedSeries<AnalyzedData>,
+    dest: &mut W,
+) -> Result<(), io::Error> {
+    let MergedSeries { meta, data } = merged;
+
+    let hdw: Vec<f64> = data
+        .iter()
+        .map(|anal| anal.hdw)
+        .filter(|hdw| !hdw.is_nan())
+        .collect();
+    let (hdw_mean, hdw_max, hdw_min) = if hdw.is_empty() {
+        (std::f64::NAN, std::f64::NAN, std::f64::NAN)
+    } else {
+        (
+            hdw.iter().sum::<f64>() / hdw.len() as f64,
+            hdw.iter().cloned().fold(std::f64::NEG_INFINITY, f64::max),
+            hdw.iter().cloned().fold(std::f64::INFINITY, f64::min),
+        )
+    };
+
+    let nan_fraction = if data.is_empty() {
+        std::f64::NAN
+    } else {
+        (data.len() - hdw.len()) as f64 / data.len() as f64
+    };
+
+    let max_blow_up = data
+        .iter()
+        .filter(|anal| !anal.blow_up_height.unpack().is_nan())
+        .max_by(|a, b| {
+            a.blow_up_height
+                .unpack()
+                .partial_cmp(&b.blow_up_height.unpack())
+                .unwrap()
+        });
+
+    writeln!(dest, "Site: {}", meta.site.description())?;
+    writeln!(dest, "Model: {}", meta.model.to_uppercase())?;
     writeln!(
         dest,
-        "# Site: {}\n# Model: {}\n# Start: {}\n# Now: {}\n# End: {}\n",
+        "Time window: {} to {}",
+        meta.start.format(GP_DATE_FORMAT),
+        meta.end.format(GP_DATE_FORMAT)
+    )?;
+    writeln!(dest, "Valid time steps: {}", data.len())?;
+    writeln!(dest, "HDW mean: {:.0}", hdw_mean)?;
+    writeln!(dest, "HDW max: {:.0}", hdw_max)?;
+    writeln!(dest, "HDW min: {:.0}", hdw_min)?;
+    writeln!(dest, "HDW missing: {:.1}%", nan_fraction * 100.0)?;
+    match max_blow_up {
+        Some(anal) => writeln!(
+            dest,
+            "Max blow up height: {:.0} m at {}",
+            anal.blow_up_height.unpack(),
+            anal.valid_time.format(GP_DATE_FORMAT)
+        )?,
+        None => writeln!(dest, "Max blow up height: NaN")?,
+    }
+
+    Ok(())
+}
+
+/// Write a header to a data file/section in gnuplot comment form.
+///
+/// This is the same header used by the files written by `save_all`, so it can be used to build
+/// compatible output files.
+pub fn write_meta_data_header<W: Write>(meta: &MetaData, dest: &mut W) -> Result<(), io::Error> {
+    dest.write_all(meta_data_header_string(meta).as_bytes())
+}
+
+/// Get the header written by `write_meta_data_header` as a `String`.
+pub fn meta_data_header_string(meta: &MetaData) -> String {
+    format!(
+        "# Site: {}\n# Model: {}\n# Start: {}\n# Now: {}\n# End: {}\n\n",
         meta.site.description(),
         meta.model,
         meta.start.format(GP_DATE_FORMAT),
         meta.now.format(GP_DATE_FORMAT),
         meta.end.format(GP_DATE_FORMAT)
-    )?;
-    Ok(())
+    )
 }
@@ -10,7 +10,9 @@
 #   output_prefix
 #
 # heredocs:
-#   $data
+#   $data - columns are valid_time lead_time blow_up_dt blow_up_height hdw ffwi haines
+#           mixing_height surface_rh surface_temperature surface_wind_speed
+#           surface_wind_direction transport_wind_speed transport_wind_direction
 #
 
 #
@@ -10,14 +10,19 @@
 #   output_prefix
 #
 # heredocs:
-#   $data
+#   $data - columns are valid_time lead_time blow_up_dt blow_up_height hdw ffwi haines
+#           mixing_height surface_rh surface_temperature surface_wind_speed
+#           surface_wind_direction transport_wind_speed transport_wind_direction
+#   $stats
 #   $hdw_climo
+#   $climo_hdw, $climo_blowupdt, ... - one for each ClimoElement, named with the lowercase name,
+#                                      only if all_climo_elements is set in the AnalysisConfig
 
 #
 # Multiplot of some experimental fire weather paramters.
 #
 reset
-set output output_prefix."/".output_name."_mrg.png"
+set output output_prefix."/".output_name
 #
 # Set up x axis data
 #
@@ -320,6 +325,7 @@ plot [start_time:end_time][0:700] \
 	""         u 1:2     w l            lc rgb "gray"         notitle, \
 	""         u 1:12    w l            lc rgb "gray"         notitle, \
 	""         u 1:7     w l            lc rgb "black"   dt 2 t "Median", \
+	$stats     u 1:2:3   w filledcurves lc rgb "black" fs transparent solid 0.2 t "Ensemble Range", \
 	$data      u 1:5     w l            lc rgb "black"        t "HDW"
 #
 # Plot the middle row which is the blow up delta_t
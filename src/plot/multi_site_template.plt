@@ -0,0 +1,73 @@
+#
+# This script assumes the following have already been set in the gnuplot environment.
+# variables:
+#   num_sites - 1 to 3
+#   start_time
+#   end_time
+#   main_title
+#   output_name
+#   output_prefix
+#   site_title_1, site_title_2, ... - one for each site
+#
+# heredocs:
+#   $data_1, $data_2, ... - one for each site, columns are valid_time lead_time blow_up_dt
+#                           blow_up_height hdw ffwi haines mixing_height surface_rh
+#                           surface_temperature surface_wind_speed surface_wind_direction
+#                           transport_wind_speed transport_wind_direction
+#
+
+#
+# Side by side plots of the fire weather parameters at several sites, one column per site.
+#
+reset
+set terminal pngcairo truecolor linewidth 2 size (400 * num_sites + 100),800
+set output output_prefix."/".output_name
+#
+# Set up x axis data
+#
+set xdata time
+set timefmt "%Y-%m-%d-%H"
+set grid
+#
+# Set up the multiplot, fill each column before moving to the next site.
+#
+set multiplot layout 3,num_sites columnsfirst title main_title font ",14"
+
+do for [i=1:num_sites] {
+    data = sprintf("$data_%d", i)
+
+    #
+    # HDW on the top row
+    #
+    set title value(sprintf("site_title_%d", i))
+    set xtics scale 0
+    set format x ''
+    unset xlabel
+    set ylabel "Hot Dry\nWindy Index"
+    set ytics 100,100,700
+    plot [start_time:end_time][0:700] @data u 1:5 w l lc rgb "black" notitle
+
+    #
+    # Blow up delta T in the middle row
+    #
+    unset title
+    set ylabel "Blow Up\nΔT [°C]"
+    set ytics 2,2,18
+    plot [start_time:end_time][0:20] @data u 1:3 w l lc rgb "black" notitle
+
+    #
+    # Blow up height on the bottom row
+    #
+    set xtics nomirror scale 1 rotate by -45
+    set format x "%m/%d %H"
+    set xlabel "Date and hour [UTC]"
+    set ylabel "Blow up\nHeight [km]"
+    set ytics 0,2
+    plot [start_time:end_time][0:10 < *] @data u 1:($4/1000) w l lc rgb "black" notitle
+}
+
+#
+# Clean up
+#
+unset multiplot
+set terminal pngcairo truecolor linewidth 2 size 800,800
@@ -0,0 +1,610 @@
+//! A pure Rust SVG plotting backend, an alternative for when gnuplot is not available.
+//!
+//! The charts are not identical to the gnuplot charts, but contain the same data series.
+use super::{analyze_message, report_error, ErrorHandler};
+use crate::{
+    error::FwxError,
+    messages::Message,
+    timeseries::{EnsembleSeries, MergedSeries, MetaData},
+    types::{AnalysisConfig, AnalyzedData},
+    util::site_label,
+};
+use bufcli::{ClimoElement, ClimoQueryInterface, Percentile};
+use chrono::{Duration, NaiveDateTime, Timelike};
+use metfor::Quantity;
+use rayon::iter::{IterBridge, ParallelBridge, ParallelIterator};
+use std::{fs::File, io::Write, path::PathBuf};
+
+/// Given an iterator over `StringData` loaded from Bufkit files, filter out any failed results
+/// and make all the plots as SVG files.
+///
+/// The data is parsed and analyzed in parallel.
+///
+/// # Arguments
+/// iter - an iterator over ensembles of model runs, make the plot and save it for each ensemble.
+/// prefix - The path to the folder where you want the plots saved.
+/// climo - The interface to the climatology database, if available.
+/// config - Options for the analysis, if `None` the defaults are used.
+/// on_error - Called with a description of each error, if `None` the errors are printed, or
+///            emitted as `tracing` events with the `tracing` feature.
+pub fn plot_all_svg<I>(
+    iter: I,
+    prefix: &str,
+    mut climo: Option<ClimoQueryInterface>,
+    config: Option<AnalysisConfig>,
+    on_error: Option<Box<ErrorHandler>>,
+) -> Result<(), FwxError>
+where
+    I: Iterator<Item = Message> + ParallelBridge + Send,
+    IterBridge<I>: ParallelIterator<Item = Message> + Send,
+{
+    let config = config.unwrap_or_default();
+    let on_error = on_error.as_deref();
+
+    let analyzed: Vec<EnsembleSeries<AnalyzedData>> = iter
+        .par_bridge()
+        .filter_map(|msg| analyze_message(msg, &config, on_error))
+        .collect();
+
+    for analyzed_data in analyzed {
+        let meta = analyzed_data.meta.clone();
+        svg_save(prefix, analyzed_data, climo.as_mut())
+            .unwrap_or_else(|err| report_error(on_error, &meta, err));
+    }
+
+    Ok(())
+}
+
+/// Given an iterator over `StringData` loaded from Bufkit files, filter out any failed results
+/// and make all the plots as SVG files.
+///
+/// Unlike `plot_all_svg`, the data is processed sequentially so the iterator does not need to be
+/// `Send`.
+///
+/// # Arguments
+/// iter - an iterator over ensembles of model runs, make the plot and save it for each ensemble.
+/// prefix - The path to the folder where you want the plots saved.
+/// climo - The interface to the climatology database, if available.
+/// config - Options for the analysis, if `None` the defaults are used.
+/// on_error - Called with a description of each error, if `None` the errors are printed, or
+///            emitted as `tracing` events with the `tracing` feature.
+pub fn save_all_svg(
+    iter: impl Iterator<Item = Message>,
+    prefix: &str,
+    mut climo: Option<ClimoQueryInterface>,
+    config: Option<AnalysisConfig>,
+    on_error: Option<Box<ErrorHandler>>,
+) -> Result<(), FwxError> {
+    let config = config.unwrap_or_default();
+    let on_error = on_error.as_deref();
+
+    iter.filter_map(|msg| analyze_message(msg, &config, on_error))
+        .for_each(|analyzed_data| {
+            let meta = analyzed_data.meta.clone();
+            svg_save(prefix, analyzed_data, climo.as_mut())
+                .unwrap_or_else(|err| report_error(on_error, &meta, err))
+        });
+
+    Ok(())
+}
+
+/// Save the ensemble and merged plots for a set of data.
+fn svg_save(
+    prefix: &str,
+    ens: EnsembleSeries<AnalyzedData>,
+    climo: Option<&mut ClimoQueryInterface>,
+) -> Result<(), FwxError> {
+    let EnsembleSeries::<AnalyzedData> { meta, .. } = &ens;
+
+    // Build the file names to save the plots to
+    let fname_ens: PathBuf = PathBuf::from(&format!(
+        "{}/{}_{}_ens.svg",
+        prefix,
+        meta.site.station_num,
+        meta.model.to_uppercase()
+    ));
+    let fname_mrg: PathBuf = PathBuf::from(&format!(
+        "{}/{}_{}_mrg.svg",
+        prefix,
+        meta.site.station_num,
+        meta.model.to_uppercase()
+    ));
+
+    File::create(&fname_ens)?.write_all(render_ensemble(&ens).as_bytes())?;
+
+    // Make a merged data and plot that too.
+    let merged = ens.merge();
+
+    let climo_data = climo.and_then(|climo_iface| {
+        climo_iface
+            .hourly_deciles(
+                &merged.meta.site,
+                &merged.meta.model,
+                ClimoElement::HDW,
+                merged.meta.start,
+                merged.meta.end,
+            )
+            .ok()
+    });
+
+    let climo_data: Option<Vec<ClimoPercentiles>> = climo_data.map(|hourly_deciles| {
+        hourly_deciles
+            .into_iter()
+            .map(|(vt, deciles)| ClimoPercentiles {
+                valid_time: vt,
+                p10: deciles.value_at_percentile(Percentile::from(10)),
+                p30: deciles.value_at_percentile(Percentile::from(30)),
+                median: deciles.value_at_percentile(Percentile::from(50)),
+                p70: deciles.value_at_percentile(Percentile::from(70)),
+                p90: deciles.value_at_percentile(Percentile::from(90)),
+            })
+            .collect()
+    });
+
+    File::create(&fname_mrg)?
+        .write_all(render_merged(&merged, climo_data.as_deref()).as_bytes())?;
+
+    Ok(())
+}
+
+/// The HDW climatology percentiles needed for the shaded bands.
+struct ClimoPercentiles {
+    valid_time: NaiveDateTime,
+    p10: f64,
+    p30: f64,
+    median: f64,
+    p70: f64,
+    p90: f64,
+}
+
+/// Draw the ensemble chart with the HDW and blow up height for every member.
+///
+/// Older model runs are drawn in lighter colors.
+fn render_ensemble(ens: &EnsembleSeries<AnalyzedData>) -> String {
+    let EnsembleSeries { meta, data } = ens;
+
+    let all_vals = || {
+        data.iter()
+            .flat_map(|(_, time_series)| time_series.as_ref())
+    };
+    let mut chart = Chart::new(
+        meta,
+        &format!("Fire Weather Parameters - {}", site_and_model(meta)),
+        all_vals().map(|anal| anal.hdw),
+        all_vals().map(|anal| anal.blow_up_height.unpack()),
+    );
+    let scale = chart.scale;
+
+    let num_members = data.len();
+    for (i, (_init_time, time_series)) in data.iter().enumerate() {
+        let frac = if num_members > 1 {
+            i as f64 / (num_members - 1) as f64
+        } else {
+            1.0
+        };
+        let color = member_color(frac);
+
+        chart.line(
+            time_series
+                .as_ref()
+                .iter()
+                .map(|anal| (scale.x(anal.valid_time), scale.y_hdw(anal.hdw))),
+            &color,
+            false,
+        );
+        chart.line(
+            time_series.as_ref().iter().map(|anal| {
+                (
+                    scale.x(anal.valid_time),
+                    scale.y_height(anal.blow_up_height.unpack()),
+                )
+            }),
+            &color,
+            true,
+        );
+    }
+
+    chart.legend(&[("HDW", "black", false), ("Blow Up Height", "black", true)]);
+
+    chart.finish()
+}
+
+/// Draw the merged chart with the HDW, climatology, and blow up height.
+fn render_merged(mrg: &MergedSeries<AnalyzedData>, climo: Option<&[ClimoPercentiles]>) -> String {
+    let MergedSeries { meta, data } = mrg;
+    let data = data.as_ref();
+
+    let climo_max = climo
+        .into_iter()
+        .flatten()
+        .map(|percentiles| percentiles.p90);
+    let mut chart = Chart::new(
+        meta,
+        &format!("Fire Weather Parameters - {}", site_and_model(meta)),
+        data.iter().map(|anal| anal.hdw).chain(climo_max),
+        data.iter().map(|anal| anal.blow_up_height.unpack()),
+    );
+    let scale = chart.scale;
+
+    if let Some(climo) = climo {
+        chart.band(
+            climo.iter().map(|cp| {
+                (
+                    scale.x(cp.valid_time),
+                    scale.y_hdw(cp.p10),
+                    scale.y_hdw(cp.p90),
+                )
+            }),
+            "#b0c4de",
+        );
+        chart.band(
+            climo.iter().map(|cp| {
+                (
+                    scale.x(cp.valid_time),
+                    scale.y_hdw(cp.p30),
+                    scale.y_hdw(cp.p70),
+                )
+            }),
+            "#6f8fbf",
+        );
+        chart.line(
+            climo
+                .iter()
+                .map(|cp| (scale.x(cp.valid_time), scale.y_hdw(cp.median))),
+            "gray",
+            true,
+        );
+    }
+
+    chart.line(
+        data.iter()
+            .map(|anal| (scale.x(anal.valid_time), scale.y_hdw(anal.hdw))),
+        "black",
+        false,
+    );
+    chart.line(
+        data.iter().map(|anal| {
+            (
+                scale.x(anal.valid_time),
+                scale.y_height(anal.blow_up_height.unpack()),
+            )
+        }),
+        "firebrick",
+        false,
+    );
+
+    let mut legend = vec![
+        ("HDW", "black", false),
+        ("Blow Up Height", "firebrick", false),
+    ];
+    if climo.is_some() {
+        legend.push(("Climo Median", "gray", true));
+    }
+    chart.legend(&legend);
+
+    chart.finish()
+}
+
+// Layout of the chart in pixels.
+const WIDTH: f64 = 900.0;
+const HEIGHT: f64 = 500.0;
+const LEFT: f64 = 80.0;
+const RIGHT: f64 = 820.0;
+const TOP: f64 = 60.0;
+const BOTTOM: f64 = 420.0;
+
+/// A chart with time on the x-axis, the HDW on the left y-axis, and the blow up height on the
+/// right y-axis.
+struct Chart {
+    scale: Scale,
+    axes: String,
+    body: String,
+}
+
+/// Maps data values to coordinates on the chart.
+#[derive(Clone, Copy)]
+struct Scale {
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    hdw_max: f64,
+    height_max_km: f64,
+}
+
+impl Scale {
+    /// Get the x coordinate for a time.
+    fn x(&self, time: NaiveDateTime) -> f64 {
+        let frac = (time - self.start).num_seconds() as f64
+            / (self.end - self.start).num_seconds().max(1) as f64;
+        LEFT + frac * (RIGHT - LEFT)
+    }
+
+    /// Get the y coordinate for an HDW value.
+    fn y_hdw(&self, hdw: f64) -> f64 {
+        BOTTOM - hdw / self.hdw_max * (BOTTOM - TOP)
+    }
+
+    /// Get the y coordinate for a blow up height in meters.
+    fn y_height(&self, height: f64) -> f64 {
+        BOTTOM - height / 1000.0 / self.height_max_km * (BOTTOM - TOP)
+    }
+}
+
+impl Chart {
+    /// Create a chart with axes scaled to fit the provided HDW and blow up height (in meters)
+    /// values.
+    fn new<I, J>(meta: &MetaData, title: &str, hdw_vals: I, height_vals: J) -> Self
+    where
+        I: Iterator<Item = f64>,
+        J: Iterator<Item = f64>,
+    {
+        let hdw_max = (hdw_vals.fold(700.0, f64::max) / 100.0).ceil() * 100.0;
+        let height_max_km = (height_vals.fold(10_000.0, f64::max) / 1000.0).ceil();
+
+        let mut chart = Chart {
+            scale: Scale {
+                start: meta.start,
+                end: meta.end,
+                hdw_max,
+                height_max_km,
+            },
+            axes: String::new(),
+            body: String::new(),
+        };
+
+        chart.draw_axes(meta, title);
+
+        chart
+    }
+
+    /// Draw a line, leaving gaps where there are NaN values.
+    fn line<I: Iterator<Item = (f64, f64)>>(&mut self, points: I, color: &str, dashed: bool) {
+        let dash = if dashed {
+            r#" stroke-dasharray="6,4""#
+        } else {
+            ""
+        };
+
+        let mut segments: Vec<Vec<(f64, f64)>> = vec![vec![]];
+        for (x, y) in points {
+            if x.is_nan() || y.is_nan() {
+                segments.push(vec![]);
+            } else {
+                segments.last_mut().unwrap().push((x, y));
+            }
+        }
+
+        for segment in segments.into_iter().filter(|segment| segment.len() > 1) {
+            self.body.push_str(&format!(
+                r#"<polyline fill="none" stroke="{}" stroke-width="2"{} points="{}"/>"#,
+                color,
+                dash,
+                points_attr(segment.into_iter())
+            ));
+            self.body.push('\n');
+        }
+    }
+
+    /// Shade the region between two lines, the points are (x, y_lower, y_upper).
+    fn band<I: Iterator<Item = (f64, f64, f64)>>(&mut self, points: I, color: &str) {
+        let points: Vec<(f64, f64, f64)> = points
+            .filter(|(x, lower, upper)| !(x.is_nan() || lower.is_nan() || upper.is_nan()))
+            .collect();
+
+        if points.len() < 2 {
+            return;
+        }
+
+        let outline = points
+            .iter()
+            .map(|&(x, lower, _)| (x, lower))
+            .chain(points.iter().rev().map(|&(x, _, upper)| (x, upper)));
+
+        self.body.push_str(&format!(
+            r#"<polygon fill="{}" fill-opacity="0.6" stroke="none" points="{}"/>"#,
+            color,
+            points_attr(outline)
+        ));
+        self.body.push('\n');
+    }
+
+    /// Add a legend in the upper right corner.
+    fn legend(&mut self, entries: &[(&str, &str, bool)]) {
+        for (i, (label, color, dashed)) in entries.iter().enumerate() {
+            let y = TOP + 15.0 + 18.0 * i as f64;
+            let dash = if *dashed {
+                r#" stroke-dasharray="6,4""#
+            } else {
+                ""
+            };
+
+            self.axes.push_str(&format!(
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="2"{}/>"#,
+                RIGHT - 160.0,
+                y,
+                RIGHT - 130.0,
+                y,
+                color,
+                dash
+            ));
+            self.axes.push_str(&format!(
+                r#"<text x="{}" y="{}" font-size="12" dominant-baseline="middle">{}</text>"#,
+                RIGHT - 125.0,
+                y,
+                escape(label)
+            ));
+            self.axes.push('\n');
+        }
+    }
+
+    /// Draw the title, frame, grid, tick labels, and the line marking now.
+    fn draw_axes(&mut self, meta: &MetaData, title: &str) {
+        let scale = self.scale;
+        let mut axes = String::new();
+
+        axes.push_str(&format!(
+            r#"<text x="{}" y="30" font-size="18" text-anchor="middle">{}</text>"#,
+            WIDTH / 2.0,
+            escape(title)
+        ));
+        axes.push('\n');
+
+        // Left axis, HDW
+        let mut hdw = 0.0;
+        while hdw <= scale.hdw_max {
+            let y = scale.y_hdw(hdw);
+            axes.push_str(&format!(
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="lightgray"/>"#,
+                LEFT, y, RIGHT, y
+            ));
+            axes.push_str(&format!(
+                r#"<text x="{}" y="{}" font-size="12" text-anchor="end" dominant-baseline="middle">{}</text>"#,
+                LEFT - 5.0,
+                y,
+                hdw
+            ));
+            axes.push('\n');
+            hdw += 100.0;
+        }
+
+        // Right axis, blow up height in km
+        let mut height = 0.0;
+        while height <= scale.height_max_km {
+            axes.push_str(&format!(
+                r#"<text x="{}" y="{}" font-size="12" dominant-baseline="middle">{}</text>"#,
+                RIGHT + 5.0,
+                scale.y_height(height * 1000.0),
+                height
+            ));
+            axes.push('\n');
+            height += 2.0;
+        }
+
+        // Time axis, ticks every 12 hours
+        let midnight =
+            scale.start - Duration::seconds(i64::from(scale.start.num_seconds_from_midnight()));
+        let mut tick = midnight;
+        while tick <= scale.end {
+            if tick >= scale.start {
+                let x = scale.x(tick);
+                axes.push_str(&format!(
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="lightgray"/>"#,
+                    x, TOP, x, BOTTOM
+                ));
+                axes.push_str(&format!(
+                    r#"<text x="{}" y="{}" font-size="12" text-anchor="end" transform="rotate(-45 {} {})">{}</text>"#,
+                    x,
+                    BOTTOM + 15.0,
+                    x,
+                    BOTTOM + 15.0,
+                    tick.format("%m/%d %H")
+                ));
+                axes.push('\n');
+            }
+            tick += Duration::hours(12);
+        }
+
+        // Axis labels
+        axes.push_str(&format!(
+            r#"<text x="20" y="{}" font-size="14" text-anchor="middle" transform="rotate(-90 20 {})">Hot Dry Windy Index</text>"#,
+            (TOP + BOTTOM) / 2.0,
+            (TOP + BOTTOM) / 2.0
+        ));
+        axes.push_str(&format!(
+            r#"<text x="{}" y="{}" font-size="14" text-anchor="middle" transform="rotate(90 {} {})">Blow Up Height [km]</text>"#,
+            WIDTH - 30.0,
+            (TOP + BOTTOM) / 2.0,
+            WIDTH - 30.0,
+            (TOP + BOTTOM) / 2.0
+        ));
+        axes.push_str(&format!(
+            r#"<text x="{}" y="{}" font-size="14" text-anchor="middle">Date and hour [UTC]</text>"#,
+            (LEFT + RIGHT) / 2.0,
+            HEIGHT - 15.0
+        ));
+        axes.push('\n');
+
+        // Frame
+        axes.push_str(&format!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="black"/>"#,
+            LEFT,
+            TOP,
+            RIGHT - LEFT,
+            BOTTOM - TOP
+        ));
+        axes.push('\n');
+
+        // Now
+        if meta.now >= scale.start && meta.now <= scale.end {
+            let x = scale.x(meta.now);
+            axes.push_str(&format!(
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black"/>"#,
+                x, TOP, x, BOTTOM
+            ));
+            axes.push('\n');
+        }
+
+        self.axes = axes;
+    }
+
+    /// Wrap up the chart as a complete SVG document.
+    fn finish(self) -> String {
+        format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                "\n",
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif">"#,
+                "\n",
+                r#"<rect width="100%" height="100%" fill="white"/>"#,
+                "\n",
+                r#"<clipPath id="plot-area"><rect x="{l}" y="{t}" width="{pw}" height="{ph}"/></clipPath>"#,
+                "\n{axes}",
+                r#"<g clip-path="url(#plot-area)">"#,
+                "\n{body}</g>\n</svg>\n"
+            ),
+            w = WIDTH,
+            h = HEIGHT,
+            l = LEFT,
+            t = TOP,
+            pw = RIGHT - LEFT,
+            ph = BOTTOM - TOP,
+            axes = self.axes,
+            body = self.body
+        )
+    }
+}
+
+/// Format points for an SVG `points` attribute.
+fn points_attr<I: Iterator<Item = (f64, f64)>>(points: I) -> String {
+    points
+        .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Get the color for an ensemble member, `frac` is 0 for the oldest and 1 for the newest.
+fn member_color(frac: f64) -> String {
+    const OLDEST: (f64, f64, f64) = (253.0, 212.0, 158.0);
+    const NEWEST: (f64, f64, f64) = (127.0, 0.0, 0.0);
+
+    let lerp = |a: f64, b: f64| (a + (b - a) * frac).round() as u8;
+
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        lerp(OLDEST.0, NEWEST.0),
+        lerp(OLDEST.1, NEWEST.1),
+        lerp(OLDEST.2, NEWEST.2)
+    )
+}
+
+/// Describe the site and model for a chart title.
+fn site_and_model(meta: &MetaData) -> String {
+    format!("{} - {}", site_label(meta), meta.model.to_uppercase())
+}
+
+/// Escape text for use in an SVG document.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
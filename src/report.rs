@@ -0,0 +1,181 @@
+//! Collect the plots into a single, self-contained HTML page for sharing.
+
+use crate::error::FwxError;
+use chrono::Utc;
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Write an HTML page with all of the `.png` images in `image_dir` embedded in it.
+///
+/// The images are sorted by file name and arranged in a grid, each with a caption of the site and
+/// model taken from the file name. The images are written to the page one at a time, so only one
+/// image is in memory at once.
+pub fn generate_html_report(
+    image_dir: &Path,
+    output_path: &Path,
+    title: &str,
+) -> Result<(), FwxError> {
+    let mut images: Vec<PathBuf> = fs::read_dir(image_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .map(|ext| ext.eq_ignore_ascii_case("png"))
+                .unwrap_or(false)
+        })
+        .collect();
+    images.sort();
+
+    let title = escape_html(title);
+    let mut dest = BufWriter::new(File::create(output_path)?);
+
+    writeln!(dest, "<!DOCTYPE html>")?;
+    writeln!(dest, "<html>")?;
+    writeln!(dest, "<head>")?;
+    writeln!(dest, "<meta charset=\"utf-8\">")?;
+    writeln!(dest, "<title>{}</title>", title)?;
+    writeln!(dest, "<style>")?;
+    writeln!(dest, "body {{ font-family: sans-serif; }}")?;
+    writeln!(
+        dest,
+        ".grid {{ display: grid; grid-template-columns: repeat(auto-fill, minmax(600px, 1fr)); \
+         gap: 1em; }}"
+    )?;
+    writeln!(dest, "figure {{ margin: 0; }}")?;
+    writeln!(dest, "img {{ width: 100%; }}")?;
+    writeln!(dest, "</style>")?;
+    writeln!(dest, "</head>")?;
+    writeln!(dest, "<body>")?;
+    writeln!(dest, "<h1>{}</h1>", title)?;
+    writeln!(
+        dest,
+        "<p>Generated {}</p>",
+        Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+    )?;
+    writeln!(dest, "<div class=\"grid\">")?;
+
+    for path in images {
+        let png = fs::read(&path)?;
+        let caption = escape_html(&caption_for(&path));
+
+        writeln!(dest, "<figure>")?;
+        writeln!(
+            dest,
+            "<img alt=\"{}\" src=\"data:image/png;base64,{}\">",
+            caption,
+            base64_encode(&png)
+        )?;
+        writeln!(dest, "<figcaption>{}</figcaption>", caption)?;
+        writeln!(dest, "</figure>")?;
+    }
+
+    writeln!(dest, "</div>")?;
+    writeln!(dest, "</body>")?;
+    writeln!(dest, "</html>")?;
+
+    dest.flush()?;
+
+    Ok(())
+}
+
+/// Make a caption from a file name like `727730_GFS_mrg.png`.
+///
+/// If the file name doesn't look like one from this library, the file name is used.
+fn caption_for(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let parts: Vec<&str> = stem.split('_').collect();
+    match parts.as_slice() {
+        [site, model, .., kind] => format!("Site {} Model {} ({})", site, model, kind),
+        _ => stem,
+    }
+}
+
+/// Escape the characters with special meaning in HTML.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Encode bytes with the standard base64 alphabet and padding.
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).cloned().unwrap_or(0);
+        let b2 = chunk.get(2).cloned().unwrap_or(0);
+
+        encoded.push(ALPHABET[(b0 >> 2) as usize] as char);
+        encoded.push(ALPHABET[(((b0 & 0x03) << 4) | (b1 >> 4)) as usize] as char);
+
+        if chunk.len() > 1 {
+            encoded.push(ALPHABET[(((b1 & 0x0f) << 2) | (b2 >> 6)) as usize] as char);
+        } else {
+            encoded.push('=');
+        }
+
+        if chunk.len() > 2 {
+            encoded.push(ALPHABET[(b2 & 0x3f) as usize] as char);
+        } else {
+            encoded.push('=');
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_rfc_4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+
+        for (input, expected) in vectors.iter() {
+            assert_eq!(
+                base64_encode(input.as_bytes()),
+                *expected,
+                "input {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn escape_html_special_characters() {
+        assert_eq!(
+            escape_html(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+        assert_eq!(escape_html("727730_GFS_mrg"), "727730_GFS_mrg");
+    }
+}
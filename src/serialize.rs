@@ -0,0 +1,89 @@
+//! Helpers for serializing and deserializing with serde.
+//!
+//! JSON does not support NaN, so missing values are represented as null.
+
+/// Serialize an `f64` with NaN values as `None`.
+pub(crate) mod nan_as_null {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(val: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if val.is_nan() { None } else { Some(*val) }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(std::f64::NAN))
+    }
+}
+
+/// Serialize a `metfor::Quantity` as a plain number with NaN values as `None`.
+pub(crate) mod quantity_nan_as_null {
+    use metfor::Quantity;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<Q, S>(val: &Q, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        Q: Quantity,
+        S: Serializer,
+    {
+        val.into_option().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, Q, D>(deserializer: D) -> Result<Q, D::Error>
+    where
+        Q: Quantity,
+        D: Deserializer<'de>,
+    {
+        Ok(Q::pack(
+            Option::<f64>::deserialize(deserializer)?.unwrap_or(std::f64::NAN),
+        ))
+    }
+}
+
+/// Serialize a `bufkit_data::SiteInfo`.
+///
+/// The state is not serialized, so it is always `None` after deserializing.
+pub(crate) mod site_info {
+    use bufkit_data::{SiteInfo, StationNumber};
+    use chrono::FixedOffset;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct SiteInfoDef {
+        station_num: u32,
+        name: Option<String>,
+        notes: Option<String>,
+        auto_download: bool,
+        /// The time zone as an offset in seconds east of UTC.
+        time_zone: Option<i32>,
+    }
+
+    pub fn serialize<S: Serializer>(site: &SiteInfo, serializer: S) -> Result<S::Ok, S::Error> {
+        SiteInfoDef {
+            station_num: u32::from(site.station_num),
+            name: site.name.clone(),
+            notes: site.notes.clone(),
+            auto_download: site.auto_download,
+            time_zone: site.time_zone.map(|tz| tz.local_minus_utc()),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SiteInfo, D::Error> {
+        let SiteInfoDef {
+            station_num,
+            name,
+            notes,
+            auto_download,
+            time_zone,
+        } = SiteInfoDef::deserialize(deserializer)?;
+
+        Ok(SiteInfo {
+            station_num: StationNumber::from(station_num),
+            name,
+            notes,
+            state: None,
+            auto_download,
+            time_zone: time_zone.and_then(FixedOffset::east_opt),
+        })
+    }
+}
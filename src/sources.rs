@@ -1,23 +1,44 @@
 //!
-//! This module has functions for loading Bufkit data from an `Archive` or from file.
+//! This module has functions for loading Bufkit data from an `Archive`, from file, or with the
+//! `http` feature from a web server.
 //!
 //! These functions produce iterators suitable for the plot functions in this library.
 //!
+//! The functions that load data past "now" take optional `ForecastHorizons` for how many days to
+//! load for each model, if they are `None` then `ForecastHorizons::default()` is used.
+//!
 
 use crate::{
+    error::FwxError,
     messages::{InnerMessage, Message},
     timeseries::{EnsembleList, MetaData},
 };
-use bufkit_data::{Archive, BufkitDataErr, Model, SiteInfo};
+use bufkit_data::{Archive, BufkitDataErr, Model, SiteInfo, StateProv, StationNumber};
 use chrono::{Duration, NaiveDateTime, Utc};
-use crossbeam::crossbeam_channel::{unbounded, Receiver};
-use std::{fs::File, io::Read, thread::spawn};
+use crossbeam::crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread::{sleep, spawn},
+};
 use strum::IntoEnumIterator;
 
 pub type StringData = EnsembleList<String>;
 
 /// Information needed for making a plot from files on disk.
+///
+/// The easiest way to create one is with a `FileDataBuilder`, or with the `serde` feature a list
+/// of them can be loaded from a config file with `load_file_data_from_toml`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FileData {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::site_info"))]
     pub site: SiteInfo,
     pub model: String,
     pub start: NaiveDateTime,
@@ -25,9 +46,162 @@ pub struct FileData {
     pub files: Vec<std::path::PathBuf>,
 }
 
+/// Load a list of `FileData` from a TOML config file.
+///
+/// Each entry is a `[[files]]` table, times are ISO 8601 strings, and the state of the site is not
+/// loaded. For example:
+///
+/// ```toml
+/// [[files]]
+/// model = "LocalWrf"
+/// start = "2017-09-02T12:00:00"
+/// end = "2017-09-05T00:00:00"
+/// files = ["local_arw_kmso/2017090212.arw_kmso.buf"]
+///
+/// [files.site]
+/// station_num = 727730
+/// name = "KMSO"
+/// auto_download = false
+/// ```
+#[cfg(feature = "serde")]
+pub fn load_file_data_from_toml(path: &Path) -> Result<Vec<FileData>, FwxError> {
+    #[derive(Deserialize)]
+    struct FileDataList {
+        files: Vec<FileData>,
+    }
+
+    let text = std::fs::read_to_string(path)?;
+    let list: FileDataList = toml::from_str(&text)?;
+
+    Ok(list.files)
+}
+
+/// A builder for `FileData`.
+///
+/// The site id, station number, model, start, and end are required.
+#[derive(Default)]
+pub struct FileDataBuilder {
+    site_id: Option<String>,
+    station_num: Option<u32>,
+    site_name: Option<String>,
+    state: Option<StateProv>,
+    notes: Option<String>,
+    model: Option<String>,
+    start: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+    files: Vec<PathBuf>,
+}
+
+impl FileDataBuilder {
+    /// Create a new, empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the site id. A `SiteInfo` has no id, so this is used as the site name unless one is
+    /// set with `site_name`.
+    pub fn site_id(mut self, site_id: &str) -> Self {
+        self.site_id = Some(site_id.to_owned());
+        self
+    }
+
+    /// Set the station number.
+    pub fn station_num(mut self, station_num: u32) -> Self {
+        self.station_num = Some(station_num);
+        self
+    }
+
+    /// Set the site name, optional.
+    pub fn site_name(mut self, site_name: &str) -> Self {
+        self.site_name = Some(site_name.to_owned());
+        self
+    }
+
+    /// Set the state or province the site is in, optional.
+    pub fn state(mut self, state: StateProv) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Set notes about the site, optional.
+    pub fn notes(mut self, notes: &str) -> Self {
+        self.notes = Some(notes.to_owned());
+        self
+    }
+
+    /// Set the model name.
+    pub fn model(mut self, model: &str) -> Self {
+        self.model = Some(model.to_owned());
+        self
+    }
+
+    /// Set the start time.
+    pub fn start(mut self, start: NaiveDateTime) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Set the end time.
+    pub fn end(mut self, end: NaiveDateTime) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Add a file to load.
+    pub fn add_file(mut self, path: PathBuf) -> Self {
+        self.files.push(path);
+        self
+    }
+
+    /// Build the `FileData`, returning an error describing the problem if any required fields
+    /// are missing or the end is before the start.
+    pub fn build(self) -> Result<FileData, String> {
+        let FileDataBuilder {
+            site_id,
+            station_num,
+            site_name,
+            state,
+            notes,
+            model,
+            start,
+            end,
+            files,
+        } = self;
+
+        let site_id = site_id.ok_or("missing site id")?;
+        let station_num = station_num.ok_or("missing station number")?;
+        let model = model.ok_or("missing model")?;
+        let start = start.ok_or("missing start time")?;
+        let end = end.ok_or("missing end time")?;
+
+        if end < start {
+            return Err(format!("end time {} is before start time {}", end, start));
+        }
+
+        Ok(FileData {
+            site: SiteInfo {
+                station_num: StationNumber::from(station_num),
+                name: Some(site_name.unwrap_or(site_id)),
+                notes,
+                state,
+                auto_download: false,
+                time_zone: None,
+            },
+            model,
+            start,
+            end,
+            files,
+        })
+    }
+}
+
 /// Load the files from disk for plotting.
+///
+/// The files may be plain text or gzip compressed. The files are read in parallel. Any files that
+/// fail to load are reported individually as errors, and the rest of the files are still sent for
+/// plotting sorted by initialization time.
 pub fn load_from_files(file_data: FileData) -> Receiver<Message> {
-    let (sender, receiver) = unbounded();
+    let (sender, receiver) = channel();
 
     spawn(move || {
         let meta = MetaData {
@@ -38,75 +212,425 @@ pub fn load_from_files(file_data: FileData) -> Receiver<Message> {
             end: file_data.end,
         };
 
-        let strings: Result<Vec<(NaiveDateTime, String)>, _> = file_data
+        let results: Vec<Result<(NaiveDateTime, String), BufkitDataErr>> = file_data
             .files
-            .iter()
-            .map(|path| {
-                let mut f = File::open(path)?;
-                let mut contents = String::new();
-                f.read_to_string(&mut contents)?;
-                Ok(contents)
-            })
-            .map(|res: Result<String, std::io::Error>| res.map_err(BufkitDataErr::from))
-            .map(|res| {
-                res.and_then(|string| {
-                    let init_time: NaiveDateTime = sounding_bufkit::BufkitData::init(&string, "")
-                        .map_err(BufkitDataErr::from)?
-                        .into_iter()
-                        .nth(0)
-                        .and_then(|(snd, _)| snd.valid_time())
-                        .ok_or(BufkitDataErr::NotEnoughData)?;
-
-                    Ok((init_time, string))
-                })
-            })
+            .par_iter()
+            .map(|path| read_bufkit_file(path).map_err(BufkitDataErr::from))
+            .map(|res| res.and_then(|string| Ok((bufkit_init_time(&string)?, string))))
             .collect();
 
-        match strings {
-            Ok(strings) => {
-                let msg = InnerMessage::StringData(StringData {
-                    meta,
-                    data: strings,
-                });
+        send_string_data(meta, results, &sender);
+    });
 
-                sender.send(Message::from(msg)).unwrap();
-            }
+    receiver
+}
+
+/// Get the initialization time of the first sounding in a bufkit file.
+fn bufkit_init_time(string: &str) -> Result<NaiveDateTime, BufkitDataErr> {
+    sounding_bufkit::BufkitData::init(string, "")
+        .map_err(BufkitDataErr::from)?
+        .into_iter()
+        .nth(0)
+        .and_then(|(snd, _)| snd.valid_time())
+        .ok_or(BufkitDataErr::NotEnoughData)
+}
+
+/// Send an error message for each failed result and the rest sorted by initialization time in a
+/// single message, if there are any.
+fn send_string_data(
+    meta: MetaData,
+    results: Vec<Result<(NaiveDateTime, String), BufkitDataErr>>,
+    sender: &Sender<Message>,
+) {
+    let site_label = meta
+        .site
+        .name
+        .clone()
+        .unwrap_or_else(|| meta.site.station_num.to_string());
+
+    let mut strings: Vec<(NaiveDateTime, String)> = Vec::with_capacity(results.len());
+    for res in results {
+        match res {
+            Ok(init_time_and_string) => strings.push(init_time_and_string),
             Err(err) => {
-                let msg = InnerMessage::BufkitDataError(err);
+                let msg = InnerMessage::error(&site_label, &meta.model, None, err);
                 sender.send(Message::from(msg)).unwrap();
             }
         }
+    }
+
+    strings.sort_by_key(|(init_time, _)| *init_time);
+
+    if !strings.is_empty() {
+        let msg = InnerMessage::StringData(StringData {
+            meta,
+            data: strings,
+        });
+
+        sender.send(Message::from(msg)).unwrap();
+    }
+}
+
+/// The timeout for connecting to and reading from a server in `load_from_urls`.
+#[cfg(feature = "http")]
+const HTTP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Download a bufkit file for plotting, see `load_from_files`.
+///
+/// The file may be plain text or gzip compressed. If the download fails, e.g. the server doesn't
+/// respond with a 200 status or the connection times out, an error is sent instead of the data.
+#[cfg(feature = "http")]
+pub fn load_from_url(
+    url: &str,
+    site: SiteInfo,
+    model: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Receiver<Message> {
+    let (sender, receiver) = channel();
+    let url = url.to_owned();
+
+    spawn(move || {
+        let meta = MetaData {
+            site,
+            model,
+            start,
+            now: start,
+            end,
+        };
+
+        let agent = http_agent();
+        let result = download_bufkit_file(&agent, &url)
+            .and_then(|string| Ok((bufkit_init_time(&string)?, string)));
+
+        send_string_data(meta, vec![result], &sender);
     });
 
     receiver
 }
 
+/// Download several bufkit files for plotting, each with its initialization time, see
+/// `load_from_url`.
+///
+/// The files are downloaded in parallel. Any that fail are reported individually as errors, and
+/// the rest are sent for plotting sorted by initialization time.
+#[cfg(feature = "http")]
+pub fn load_from_urls(
+    urls: Vec<(String, NaiveDateTime)>,
+    site: SiteInfo,
+    model: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Receiver<Message> {
+    let (sender, receiver) = channel();
+
+    spawn(move || {
+        let meta = MetaData {
+            site,
+            model,
+            start,
+            now: start,
+            end,
+        };
+
+        let agent = http_agent();
+        let results: Vec<Result<(NaiveDateTime, String), BufkitDataErr>> = urls
+            .par_iter()
+            .map(|(url, init_time)| {
+                download_bufkit_file(&agent, url).map(|string| (*init_time, string))
+            })
+            .collect();
+
+        send_string_data(meta, results, &sender);
+    });
+
+    receiver
+}
+
+/// Create an HTTP client with the timeouts set.
+#[cfg(feature = "http")]
+fn http_agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(HTTP_TIMEOUT).build()
+}
+
+/// Download a bufkit file, decompressing it if it is gzipped, see `decode_bufkit_bytes`.
+#[cfg(feature = "http")]
+fn download_bufkit_file(agent: &ureq::Agent, url: &str) -> Result<String, BufkitDataErr> {
+    let to_io_err = std::io::Error::other;
+
+    let response = agent.get(url).call().map_err(|err| match err {
+        ureq::Error::Status(code, _) => to_io_err(format!("HTTP status {} from {}", code, url)),
+        ureq::Error::Transport(err) => to_io_err(format!("error fetching {}: {}", url, err)),
+    })?;
+
+    if response.status() != 200 {
+        return Err(BufkitDataErr::from(to_io_err(format!(
+            "HTTP status {} from {}",
+            response.status(),
+            url
+        ))));
+    }
+
+    let mut bytes = vec![];
+    response.into_reader().read_to_end(&mut bytes)?;
+
+    Ok(decode_bufkit_bytes(bytes, url.ends_with(".gz"))?)
+}
+
+/// Load all the bufkit files in a directory for plotting, see `load_from_files`.
+///
+/// Files with a `.buf` or `.bufkit` extension, optionally followed by `.gz`, are loaded. The
+/// directory is not searched recursively. If the directory cannot be read, a single error is sent.
+pub fn load_from_directory(
+    dir: &Path,
+    site: SiteInfo,
+    model: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Receiver<Message> {
+    match find_bufkit_files(dir) {
+        Ok(files) => load_from_files(FileData {
+            site,
+            model,
+            start,
+            end,
+            files,
+        }),
+        Err(err) => {
+            let (sender, receiver) = channel();
+
+            let site_label = site
+                .name
+                .clone()
+                .unwrap_or_else(|| site.station_num.to_string());
+            let msg = InnerMessage::error(&site_label, &model, None, BufkitDataErr::from(err));
+            sender.send(Message::from(msg)).unwrap();
+
+            receiver
+        }
+    }
+}
+
+/// Find the bufkit files in a directory, sorted by path.
+fn find_bufkit_files(dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    const EXTENSIONS: &[&str] = &[".buf", ".bufkit", ".buf.gz", ".bufkit.gz"];
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_lowercase())
+                .map(|name| EXTENSIONS.iter().any(|ext| name.ends_with(ext)))
+                .unwrap_or(false)
+        })
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
+
+/// Read a bufkit file, decompressing it first if it is gzipped.
+///
+/// Files are considered gzipped if they have a `.gz` extension or start with the gzip magic
+/// number. If decompression fails, the file is read as plain text.
+fn read_bufkit_file(path: &Path) -> Result<String, std::io::Error> {
+    let mut bytes = vec![];
+    File::open(path)?.read_to_end(&mut bytes)?;
+
+    let is_gz = path.extension().map(|ext| ext == "gz").unwrap_or(false);
+    decode_bufkit_bytes(bytes, is_gz)
+}
+
+/// Decode the contents of a bufkit file, decompressing it first if `is_gz` is set or it starts
+/// with the gzip magic number. If decompression fails, the contents are read as plain text.
+fn decode_bufkit_bytes(bytes: Vec<u8>, is_gz: bool) -> Result<String, std::io::Error> {
+    const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+    if is_gz || bytes.starts_with(GZIP_MAGIC) {
+        let mut contents = String::new();
+        if GzDecoder::new(bytes.as_slice())
+            .read_to_string(&mut contents)
+            .is_ok()
+        {
+            return Ok(contents);
+        }
+    }
+
+    String::from_utf8(bytes)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+}
+
 /// Load model initialization times for the given site and model assuming the current time is
 /// the time given by the `time` parameter.
-pub fn load_for_site_and_date_and_time<'a>(
-    arch: &'a Archive,
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(site = site, model = model.as_static_str()))
+)]
+pub fn load_for_site_and_date_and_time(
+    arch: &Archive,
     site: &str,
     model: Model,
     time: NaiveDateTime,
     days_back: i64,
+    horizons: Option<&ForecastHorizons>,
+) -> Receiver<Message> {
+    let start = time - Duration::days(days_back);
+    let end = time + Duration::days(num_days(model, &horizons.copied().unwrap_or_default()));
+
+    load_range_with_now(arch, site, model, start, Some(time), end)
+}
+
+/// Load all the model initialization times for the given site and model with valid times that
+/// overlap the range from `start` to `end`.
+///
+/// Since there is no meaningful "now" for a historical range, the `now` member of the `MetaData`
+/// is set to `start`.
+pub fn load_range(
+    arch: &Archive,
+    site: &str,
+    model: Model,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Receiver<Message> {
+    load_range_with_now(arch, site, model, start, None, end)
+}
+
+/// Load only the listed model initialization times for the given site and model.
+///
+/// Any runs that fail to load are reported individually as errors, and the rest of the runs are
+/// still sent for plotting. The `start` of the `MetaData` is the earliest initialization time,
+/// which is also used as `now`, and the `end` is the end of the latest model run.
+pub fn load_specific_runs(
+    arch: &Archive,
+    site: &str,
+    model: Model,
+    init_times: &[NaiveDateTime],
+    horizons: Option<&ForecastHorizons>,
+) -> Receiver<Message> {
+    let root = arch.root().to_path_buf();
+    let site = site.to_owned();
+    let mut init_times = init_times.to_vec();
+    init_times.sort();
+    init_times.dedup();
+    let (sender, receiver) = channel();
+
+    let (start, last_init) = match (init_times.first(), init_times.last()) {
+        (Some(&start), Some(&last_init)) => (start, last_init),
+        _ => return receiver,
+    };
+    let end = last_init + Duration::days(num_days(model, &horizons.copied().unwrap_or_default()));
+
+    spawn(move || {
+        let arch = match Archive::connect(&root) {
+            Ok(arch) => arch,
+            Err(err) => {
+                sender
+                    .send(Message::from(InnerMessage::error(
+                        &site,
+                        model.as_static_str(),
+                        None,
+                        err,
+                    )))
+                    .unwrap();
+                return;
+            }
+        };
+
+        let site_info = match arch
+            .station_num_for_id(&site, model)
+            .and_then(|stn_num| arch.site(stn_num).ok_or(BufkitDataErr::NotInIndex))
+        {
+            Ok(site_info) => site_info,
+            Err(err) => {
+                sender
+                    .send(Message::from(InnerMessage::error(
+                        &site,
+                        model.as_static_str(),
+                        None,
+                        err,
+                    )))
+                    .unwrap();
+                return;
+            }
+        };
+
+        let mut data: Vec<(NaiveDateTime, String)> = Vec::with_capacity(init_times.len());
+        for init_time in init_times {
+            match arch.retrieve(site_info.station_num, model, init_time) {
+                Ok(string) => data.push((init_time, string)),
+                Err(err) => {
+                    sender
+                        .send(Message::from(InnerMessage::error(
+                            &site,
+                            model.as_static_str(),
+                            Some(init_time),
+                            err,
+                        )))
+                        .unwrap();
+                }
+            }
+        }
+
+        if !data.is_empty() {
+            let meta = MetaData {
+                site: site_info,
+                model: model.as_static_str().to_owned(),
+                start,
+                now: start,
+                end,
+            };
+
+            let mut string_data = StringData { meta, data };
+            string_data.sort_by_init_time();
+            let msg = InnerMessage::StringData(string_data);
+
+            sender.send(Message::from(msg)).unwrap();
+        }
+    });
+
+    receiver
+}
+
+/// Load all the model initialization times for the given site and model with valid times that
+/// overlap the range from `start` to `end`, using `now` as the current time.
+///
+/// If there is no `now`, `start` is used as the current time and errors are reported without a
+/// time.
+fn load_range_with_now(
+    arch: &Archive,
+    site: &str,
+    model: Model,
+    start: NaiveDateTime,
+    now: Option<NaiveDateTime>,
+    end: NaiveDateTime,
 ) -> Receiver<Message> {
     let root = arch.root().to_path_buf();
     let site = site.to_owned();
-    let (sender, receiver) = unbounded();
+    let (sender, receiver) = channel();
+
+    #[cfg(feature = "tracing")]
+    let span = tracing::Span::current();
 
     spawn(move || {
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+
         let arch = match Archive::connect(&root) {
             Ok(arch) => arch,
             Err(err) => {
                 sender
-                    .send(Message::from(InnerMessage::BufkitDataError(err)))
+                    .send(Message::from(InnerMessage::error(
+                        &site,
+                        model.as_static_str(),
+                        now,
+                        err,
+                    )))
                     .unwrap();
                 return;
             }
         };
 
-        let start = time - Duration::days(days_back);
-        let end = time + Duration::days(num_days(model));
         let site_info = match arch
             .station_num_for_id(&site, model)
             .and_then(|stn_num| arch.site(stn_num).ok_or(BufkitDataErr::NotInIndex))
@@ -114,7 +638,12 @@ pub fn load_for_site_and_date_and_time<'a>(
             Ok(site_info) => site_info,
             Err(err) => {
                 sender
-                    .send(Message::from(InnerMessage::BufkitDataError(err)))
+                    .send(Message::from(InnerMessage::error(
+                        &site,
+                        model.as_static_str(),
+                        now,
+                        err,
+                    )))
                     .unwrap();
                 return;
             }
@@ -135,21 +664,33 @@ pub fn load_for_site_and_date_and_time<'a>(
                     })
                     .collect();
 
+                trace_debug!("retrieved {} model runs", data.len());
+                if data.is_empty() {
+                    trace_warn!("no model runs for {} at {}", model.as_static_str(), site);
+                }
+
                 let meta = MetaData {
                     site: site_info,
                     model: model.as_static_str().to_owned(),
                     start,
-                    now: time,
+                    now: now.unwrap_or(start),
                     end,
                 };
 
-                let msg = InnerMessage::StringData(StringData { meta, data });
+                let mut string_data = StringData { meta, data };
+                string_data.sort_by_init_time();
+                let msg = InnerMessage::StringData(string_data);
 
                 sender.send(Message::from(msg)).unwrap();
             }
             Err(err) => {
                 sender
-                    .send(Message::from(InnerMessage::BufkitDataError(err)))
+                    .send(Message::from(InnerMessage::error(
+                        &site,
+                        model.as_static_str(),
+                        now,
+                        err,
+                    )))
                     .unwrap();
             }
         }
@@ -159,29 +700,397 @@ pub fn load_for_site_and_date_and_time<'a>(
 }
 
 /// Load all the model initialization times valid before now and going days back.
-pub fn load_site<'a>(
-    arch: &'a Archive,
+pub fn load_site(
+    arch: &Archive,
     site: &str,
     model: Model,
     days_back: i64,
+    horizons: Option<&ForecastHorizons>,
 ) -> Receiver<Message> {
     let now = Utc::now().naive_utc();
 
-    load_for_site_and_date_and_time(arch, site, model, now, days_back)
+    load_for_site_and_date_and_time(arch, site, model, now, days_back, horizons)
+}
+
+/// Load all the model initialization times valid before now and going days back for every model
+/// with data for the site.
+///
+/// Models the site has no data for in the archive are skipped instead of sending an error. The
+/// messages for each model are sent in turn, all the GFS runs before the NAM runs, etc.
+pub fn load_site_all_models(
+    arch: &Archive,
+    site: &str,
+    days_back: i64,
+    horizons: Option<&ForecastHorizons>,
+) -> Receiver<Message> {
+    let receivers: Vec<Receiver<Message>> = Model::iter()
+        .filter(|&model| arch.station_num_for_id(site, model).is_ok())
+        .map(|model| load_site(arch, site, model, days_back, horizons))
+        .collect();
+
+    let (sender, receiver) = channel();
+
+    spawn(move || {
+        for msg in receivers.into_iter().flat_map(|recv| recv.into_iter()) {
+            if sender.send(msg).is_err() {
+                // The receiver hung up, so no one is listening.
+                return;
+            }
+        }
+    });
+
+    receiver
 }
 
 /// Load all the model initialization times for all sites and models in the provided archive valid
 /// before now and going days back.
-pub fn load_all_sites_and_models(arch: &Archive, days_back: i64) -> Receiver<Message> {
+///
+/// If there is a `cache`, model runs already in it are not read from the archive again, except the
+/// most recent runs that may still be being written, and any runs that are read are added to it.
+/// The same runs are loaded with or without a cache.
+pub fn load_all_sites_and_models(
+    arch: &Archive,
+    days_back: i64,
+    horizons: Option<&ForecastHorizons>,
+    cache: Option<&BufkitStringCache>,
+) -> Receiver<Message> {
+    load_sites_for_models(
+        arch,
+        Model::iter().collect(),
+        days_back,
+        horizons,
+        None,
+        None,
+        cache.cloned(),
+    )
+}
+
+/// Load all the model initialization times for the sites in the provided archive that match
+/// `site_filter` for all models valid before now and going days back.
+///
+/// The filter is applied to each `SiteInfo` before querying the archive for data. Optional fields,
+/// like `state`, may be `None`, so a filter on the state should decide what to do with sites that
+/// have no state. For example, `|site| site.state == Some(StateProv::MT)` skips sites with a
+/// missing state.
+pub fn load_all_sites_and_models_filtered(
+    arch: &Archive,
+    days_back: i64,
+    site_filter: Box<dyn Fn(&SiteInfo) -> bool + Send>,
+    horizons: Option<&ForecastHorizons>,
+) -> Receiver<Message> {
+    load_sites_for_models(
+        arch,
+        Model::iter().collect(),
+        days_back,
+        horizons,
+        Some(site_filter),
+        None,
+        None,
+    )
+}
+
+/// Load all the model initialization times for all sites in the provided archive for a single
+/// model valid before now and going days back.
+pub fn load_for_model(
+    arch: &Archive,
+    model: Model,
+    days_back: i64,
+    horizons: Option<&ForecastHorizons>,
+) -> Receiver<Message> {
+    load_sites_for_models(arch, vec![model], days_back, horizons, None, None, None)
+}
+
+/// Load all the model initialization times for all sites and models in the provided archive valid
+/// before now and going days back, reporting progress along the way.
+///
+/// The `progress` callback is called after the data for each site is sent with the number of
+/// sites dispatched so far and the total number of sites.
+pub fn load_all_sites_and_models_with_progress(
+    arch: &Archive,
+    days_back: i64,
+    progress: Box<dyn Fn(usize, usize) + Send>,
+    horizons: Option<&ForecastHorizons>,
+) -> Receiver<Message> {
+    load_sites_for_models(
+        arch,
+        Model::iter().collect(),
+        days_back,
+        horizons,
+        None,
+        Some(progress),
+        None,
+    )
+}
+
+/// The number of days after "now" to load for each model.
+///
+/// These depend on the length of the model output, which changes when the models are upgraded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ForecastHorizons {
+    pub gfs: i64,
+    pub nam: i64,
+    pub nam4km: i64,
+}
+
+impl Default for ForecastHorizons {
+    fn default() -> Self {
+        ForecastHorizons {
+            gfs: 7,
+            nam: 4,
+            nam4km: 3,
+        }
+    }
+}
+
+/// Retrieve all the model runs initialized from `first_init` to `end`, the runs with valid times
+/// that may overlap the requested range.
+///
+/// The same runs are selected whether or not there is a `cache`. If there is one, runs initialized
+/// before `refresh_after` are taken from it when they are in it, and every run read from the
+/// archive is added to it. Runs initialized at or after `refresh_after` may still be being written
+/// to the archive, so they are always read again and replace any copy in the cache.
+fn retrieve_runs(
+    arch: &Archive,
+    cache: Option<&BufkitStringCache>,
+    station_num: StationNumber,
+    model: Model,
+    first_init: NaiveDateTime,
+    refresh_after: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Result<Vec<(NaiveDateTime, String)>, BufkitDataErr> {
+    let init_times = with_retry(RETRIES, RETRY_DELAY, || arch.inventory(station_num, model))?;
+
+    let mut data = vec![];
+    for init_time in init_times
+        .into_iter()
+        .filter(|&init_time| init_time >= first_init && init_time <= end)
+    {
+        let cached = cache
+            .filter(|_| init_time < refresh_after)
+            .and_then(|cache| cache.get(station_num, model, init_time));
+
+        let string = match cached {
+            Some(string) => string,
+            None => {
+                let string = with_retry(RETRIES, RETRY_DELAY, || {
+                    arch.retrieve(station_num, model, init_time)
+                })?;
+                if let Some(cache) = cache {
+                    cache.insert(station_num, model, init_time, string.clone());
+                }
+                string
+            }
+        };
+
+        data.push((init_time, string));
+    }
+
+    Ok(data)
+}
+
+/// Model runs initialized less than this many hours before now may still be being written to the
+/// archive, so they are not served from a `BufkitStringCache`.
+const CACHE_REFRESH_HOURS: i64 = 6;
+
+/// A cache of the model runs loaded from the archive, so they are not read from the archive and
+/// decompressed again.
+///
+/// This is most useful for long running processes, or frequent cron jobs, that load the same sites
+/// repeatedly, e.g. to update the plots every hour. The runs are kept in memory, and if the cache
+/// was created with `with_directory` they are also saved as gzipped files in that directory so
+/// they are available to later processes. The cache only saves the archive reads and
+/// decompression, each load still gets its own copy of the runs.
+///
+/// Runs initialized in the last few hours may still be being written to the archive, so they are
+/// always read again and replaced in the cache. Cloning the cache creates another handle to the
+/// same storage, so it can be shared with the loading threads.
+#[derive(Clone, Debug, Default)]
+pub struct BufkitStringCache {
+    cache: Arc<Mutex<HashMap<CacheKey, String>>>,
+    dir: Option<PathBuf>,
+}
+
+/// The site, model, and initialization time of a model run in a `BufkitStringCache`.
+type CacheKey = (StationNumber, Model, NaiveDateTime);
+
+/// The format of the initialization time in the names of the files in a cache directory.
+const CACHE_FILE_DATE_FORMAT: &str = "%Y%m%d%H";
+
+impl BufkitStringCache {
+    /// Create a new, empty cache that is only kept in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a cache that is also stored in `dir`, which is created if it doesn't exist.
+    ///
+    /// Any runs saved in the directory by an earlier cache are used.
+    pub fn with_directory(dir: &Path) -> Result<Self, FwxError> {
+        std::fs::create_dir_all(dir)?;
+
+        Ok(BufkitStringCache {
+            cache: Arc::default(),
+            dir: Some(dir.to_path_buf()),
+        })
+    }
+
+    /// The number of model runs in memory, this does not include runs only in the directory.
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().len()
+    }
+
+    /// Check if there are no model runs in memory.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove everything from the cache, including any files in the directory.
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+        self.remove_files(|_| true);
+    }
+
+    /// Get a model run from the cache, loading it from the directory if it isn't in memory.
+    fn get(
+        &self,
+        station_num: StationNumber,
+        model: Model,
+        init_time: NaiveDateTime,
+    ) -> Option<String> {
+        let key = (station_num, model, init_time);
+        if let Some(string) = self.cache.lock().unwrap().get(&key) {
+            return Some(string.clone());
+        }
+
+        let path = self.file_path(station_num, model, init_time)?;
+        let string = read_bufkit_file(&path).ok()?;
+        self.cache.lock().unwrap().insert(key, string.clone());
+
+        Some(string)
+    }
+
+    /// Add a model run to the cache, and save it in the directory if there is one.
+    fn insert(
+        &self,
+        station_num: StationNumber,
+        model: Model,
+        init_time: NaiveDateTime,
+        string: String,
+    ) {
+        if let Some(path) = self.file_path(station_num, model, init_time) {
+            if let Err(err) = write_gzipped(&path, &string) {
+                trace_warn!("unable to save {} to the cache: {}", path.display(), err);
+            }
+        }
+
+        self.cache
+            .lock()
+            .unwrap()
+            .insert((station_num, model, init_time), string);
+    }
+
+    /// Remove all the runs for `model` initialized before `first_init`.
+    fn prune(&self, model: Model, first_init: NaiveDateTime) {
+        self.cache
+            .lock()
+            .unwrap()
+            .retain(|&(_, m, init_time), _| m != model || init_time >= first_init);
+
+        self.remove_files(|(m, init_time)| m == model.as_static_str() && init_time < first_init);
+    }
+
+    /// The path of the file for a model run in the directory, `None` if there is no directory.
+    fn file_path(
+        &self,
+        station_num: StationNumber,
+        model: Model,
+        init_time: NaiveDateTime,
+    ) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| {
+            dir.join(format!(
+                "{}_{}_{}.buf.gz",
+                station_num,
+                model.as_static_str(),
+                init_time.format(CACHE_FILE_DATE_FORMAT)
+            ))
+        })
+    }
+
+    /// Remove the files in the directory whose model and initialization time match `pred`, any
+    /// files with names not made by `file_path` are left alone.
+    fn remove_files<F: Fn((&str, NaiveDateTime)) -> bool>(&self, pred: F) {
+        let entries = match self.dir.as_ref().map(std::fs::read_dir) {
+            Some(Ok(entries)) => entries,
+            _ => return,
+        };
+
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            let name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let parts: Vec<&str> = name.trim_end_matches(".buf.gz").split('_').collect();
+            if let [_station_num, model, init_time] = parts.as_slice() {
+                let init_time =
+                    NaiveDateTime::parse_from_str(&format!("{}00", init_time), "%Y%m%d%H%M");
+                if let Ok(init_time) = init_time {
+                    if pred((model, init_time)) {
+                        let _ = std::fs::remove_file(&path);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Write a string to a gzipped file.
+fn write_gzipped(path: &Path, string: &str) -> Result<(), std::io::Error> {
+    let mut encoder = GzEncoder::new(File::create(path)?, Compression::default());
+    encoder.write_all(string.as_bytes())?;
+    encoder.finish()?;
+
+    Ok(())
+}
+
+/// A predicate for selecting which sites to load.
+type SiteFilter = Box<dyn Fn(&SiteInfo) -> bool + Send>;
+
+/// Load all the model initialization times for all the sites in the archive for each of the
+/// `models`, valid before now and going days back.
+///
+/// If there is a `site_filter`, only the sites it returns `true` for are loaded. If there is a
+/// `cache`, it is used instead of the archive for any model runs already in it, except the most
+/// recent runs that may still be being written, see `BufkitStringCache`.
+fn load_sites_for_models(
+    arch: &Archive,
+    models: Vec<Model>,
+    days_back: i64,
+    horizons: Option<&ForecastHorizons>,
+    site_filter: Option<SiteFilter>,
+    progress: Option<Box<dyn Fn(usize, usize) + Send>>,
+    cache: Option<BufkitStringCache>,
+) -> Receiver<Message> {
     let root = arch.root().to_path_buf();
-    let (sender, receiver) = unbounded();
+    let horizons = horizons.copied().unwrap_or_default();
+    let (sender, receiver) = channel();
+    let models_label = models
+        .iter()
+        .map(|model| model.as_static_str())
+        .collect::<Vec<_>>()
+        .join(",");
 
     spawn(move || {
         let arch = match Archive::connect(&root) {
             Ok(arch) => arch,
             Err(err) => {
                 sender
-                    .send(Message::from(InnerMessage::BufkitDataError(err)))
+                    .send(Message::from(InnerMessage::error(
+                        "all sites",
+                        &models_label,
+                        None,
+                        err,
+                    )))
                     .unwrap();
                 return;
             }
@@ -190,34 +1099,72 @@ pub fn load_all_sites_and_models(arch: &Archive, days_back: i64) -> Receiver<Mes
         let now = Utc::now().naive_utc();
         let start = now - Duration::days(days_back);
 
-        for model in Model::iter() {
-            let sites_ids = match arch.sites_and_ids_for(model) {
-                Ok(sites_ids) => sites_ids,
+        let mut models_sites_ids = vec![];
+        for model in models {
+            match arch.sites_and_ids_for(model) {
+                Ok(mut sites_ids) => {
+                    if let Some(site_filter) = site_filter.as_ref() {
+                        sites_ids.retain(|(site_info, _site_id)| site_filter(site_info));
+                    }
+                    models_sites_ids.push((model, sites_ids));
+                }
                 Err(err) => {
                     sender
-                        .send(Message::from(InnerMessage::BufkitDataError(err)))
+                        .send(Message::from(InnerMessage::error(
+                            "all sites",
+                            model.as_static_str(),
+                            None,
+                            err,
+                        )))
                         .unwrap();
                     return;
                 }
-            };
+            }
+        }
+
+        let total_sites: usize = models_sites_ids
+            .iter()
+            .map(|(_model, sites_ids)| sites_ids.len())
+            .sum();
+        let mut sites_dispatched = 0;
+
+        for (model, sites_ids) in models_sites_ids {
+            let end = now + Duration::days(num_days(model, &horizons));
+
+            // Runs initialized this long before the start may still have valid times after it,
+            // and nothing before this will be loaded again so there is no need to keep it cached.
+            let first_init = start - Duration::days(num_days(model, &horizons));
+            let refresh_after = now - Duration::hours(CACHE_REFRESH_HOURS);
+            if let Some(cache) = cache.as_ref() {
+                cache.prune(model, first_init);
+            }
 
-            let end = now + Duration::days(num_days(model));
+            for (site_info, site_id) in sites_ids.into_iter() {
+                let data = retrieve_runs(
+                    &arch,
+                    cache.as_ref(),
+                    site_info.station_num,
+                    model,
+                    first_init,
+                    refresh_after,
+                    end,
+                );
 
-            for (site_info, _site_id) in sites_ids.into_iter() {
-                match arch.retrieve_all_valid_in(site_info.station_num, model, start, end) {
+                match data {
                     Ok(data) => {
-                        let data: Vec<(NaiveDateTime, String)> = data
-                            .filter_map(|string| {
-                                let init_time: NaiveDateTime =
-                                    sounding_bufkit::BufkitData::init(&string, "")
-                                        .ok()?
-                                        .into_iter()
-                                        .nth(0)
-                                        .and_then(|(snd, _)| snd.valid_time())?;
-
-                                Some((init_time, string))
-                            })
-                            .collect();
+                        trace_debug!(
+                            "retrieved {} model runs for {} at {}",
+                            data.len(),
+                            model.as_static_str(),
+                            site_info.station_num
+                        );
+                        if data.is_empty() {
+                            trace_warn!(
+                                "no model runs for {} at {}",
+                                model.as_static_str(),
+                                site_info.station_num
+                            );
+                        }
 
                         let meta = MetaData {
                             site: site_info,
@@ -227,16 +1174,28 @@ pub fn load_all_sites_and_models(arch: &Archive, days_back: i64) -> Receiver<Mes
                             end,
                         };
 
-                        let msg = InnerMessage::StringData(StringData { meta, data });
+                        let mut string_data = StringData { meta, data };
+                        string_data.sort_by_init_time();
+                        let msg = InnerMessage::StringData(string_data);
 
                         sender.send(Message::from(msg)).unwrap();
                     }
                     Err(err) => {
                         sender
-                            .send(Message::from(InnerMessage::BufkitDataError(err)))
+                            .send(Message::from(InnerMessage::error(
+                                &site_id,
+                                model.as_static_str(),
+                                None,
+                                err,
+                            )))
                             .unwrap();
                     }
                 }
+
+                sites_dispatched += 1;
+                if let Some(progress) = progress.as_ref() {
+                    progress(sites_dispatched, total_sites);
+                }
             }
         }
     });
@@ -244,11 +1203,242 @@ pub fn load_all_sites_and_models(arch: &Archive, days_back: i64) -> Receiver<Mes
     receiver
 }
 
+/// Separate the loaded data from the errors in the messages produced by the loading functions.
+///
+/// This is useful for consuming the messages some other way than with the plot and save
+/// functions in this library.
+pub fn from_messages<I: Iterator<Item = Message>>(
+    messages: I,
+) -> (Vec<StringData>, Vec<BufkitDataErr>) {
+    let mut data = vec![];
+    let mut errors = vec![];
+
+    for msg in messages {
+        match msg.payload() {
+            InnerMessage::StringData(mut string_data) => {
+                string_data.sort_by_init_time();
+                data.push(string_data)
+            }
+            InnerMessage::BufkitDataError(err) => errors.push(err.into_cause()),
+        }
+    }
+
+    (data, errors)
+}
+
+/// Count the successfully loaded data and the errors from one of the loading functions, without
+/// analyzing the data.
+///
+/// This waits for the loading to finish, so it is useful for checking an archive is complete.
+/// Returns `(success_count, error_count)`.
+pub fn count_messages(recv: Receiver<Message>) -> (usize, usize) {
+    recv.into_iter()
+        .fold((0, 0), |(successes, errors), msg| match msg.payload() {
+            InnerMessage::StringData(_) => (successes + 1, errors),
+            InnerMessage::BufkitDataError(_) => (successes, errors + 1),
+        })
+}
+
+/// The default time `drain_receiver` waits for each message before giving up.
+pub const DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Collect all the messages from one of the loading functions, waiting for the loading to finish.
+///
+/// # Panics
+///
+/// Panics if no message arrives for `DRAIN_TIMEOUT` before the loading thread finishes, use
+/// `drain_receiver_with_timeout` for a different timeout.
+pub fn drain_receiver(recv: Receiver<Message>) -> Vec<Message> {
+    drain_receiver_with_timeout(recv, DRAIN_TIMEOUT)
+}
+
+/// Collect all the messages from one of the loading functions, waiting for the loading to finish.
+///
+/// # Panics
+///
+/// Panics if no message arrives for `timeout` before the loading thread finishes.
+pub fn drain_receiver_with_timeout(
+    recv: Receiver<Message>,
+    timeout: std::time::Duration,
+) -> Vec<Message> {
+    let mut messages = vec![];
+
+    loop {
+        match recv.recv_timeout(timeout) {
+            Ok(msg) => messages.push(msg),
+            Err(RecvTimeoutError::Disconnected) => return messages,
+            Err(RecvTimeoutError::Timeout) => panic!(
+                "no message received for {:?} after {} messages, the loading thread may be stuck",
+                timeout,
+                messages.len()
+            ),
+        }
+    }
+}
+
+/// A summary of the data available for each model in an archive, see `summarize_archive`.
+#[derive(Clone, Debug)]
+pub struct ArchiveSummary {
+    pub models: Vec<ModelSummary>,
+}
+
+/// A summary of the data available for one model in an archive.
+#[derive(Clone, Debug)]
+pub struct ModelSummary {
+    pub model: String,
+    /// The number of sites with data for this model.
+    pub site_count: usize,
+    /// The earliest initialization time for any site, `None` if there is no data.
+    pub earliest_init_time: Option<NaiveDateTime>,
+    /// The latest initialization time for any site, `None` if there is no data.
+    pub latest_init_time: Option<NaiveDateTime>,
+    /// The site ids, sorted by station number.
+    pub site_ids: Vec<String>,
+    /// The ids of sites with fewer than half the median number of model runs for this model,
+    /// sorted by station number.
+    pub sparse_site_ids: Vec<String>,
+}
+
+/// Summarize the sites and time ranges available in the archive for each model.
+///
+/// Only the archive index is queried, none of the model runs are loaded, so this is quick even
+/// for archives with hundreds of sites.
+pub fn summarize_archive(arch: &Archive) -> Result<ArchiveSummary, FwxError> {
+    let mut models = vec![];
+
+    for model in Model::iter() {
+        let mut sites_ids = with_retry(RETRIES, RETRY_DELAY, || arch.sites_and_ids_for(model))?;
+        sites_ids.sort_by_key(|(site_info, _site_id)| u32::from(site_info.station_num));
+
+        let mut earliest_init_time: Option<NaiveDateTime> = None;
+        let mut latest_init_time: Option<NaiveDateTime> = None;
+        let mut run_counts = Vec::with_capacity(sites_ids.len());
+        for (site_info, _site_id) in sites_ids.iter() {
+            let init_times = with_retry(RETRIES, RETRY_DELAY, || {
+                arch.inventory(site_info.station_num, model)
+            })?;
+
+            if let Some(&first) = init_times.iter().min() {
+                earliest_init_time = Some(earliest_init_time.map_or(first, |t| t.min(first)));
+            }
+            if let Some(&last) = init_times.iter().max() {
+                latest_init_time = Some(latest_init_time.map_or(last, |t| t.max(last)));
+            }
+
+            run_counts.push(init_times.len());
+        }
+
+        let median_runs = {
+            let mut counts = run_counts.clone();
+            counts.sort_unstable();
+            counts.get(counts.len() / 2).cloned().unwrap_or(0)
+        };
+
+        let sparse_site_ids = sites_ids
+            .iter()
+            .zip(run_counts)
+            .filter(|(_, count)| count * 2 < median_runs)
+            .map(|((_, site_id), _)| site_id.clone())
+            .collect();
+
+        models.push(ModelSummary {
+            model: model.as_static_str().to_owned(),
+            site_count: sites_ids.len(),
+            earliest_init_time,
+            latest_init_time,
+            site_ids: sites_ids.into_iter().map(|(_, site_id)| site_id).collect(),
+            sparse_site_ids,
+        });
+    }
+
+    Ok(ArchiveSummary { models })
+}
+
+/// The maximum number of messages waiting in a channel before the loading thread pauses for the
+/// consumer to catch up, this keeps the memory use bounded when loading large archives.
+const CHANNEL_BUFFER_SIZE: usize = 64;
+
+/// Create a channel for sending the loaded data.
+fn channel() -> (Sender<Message>, Receiver<Message>) {
+    bounded(CHANNEL_BUFFER_SIZE)
+}
+
+/// The number of times to retry a failed archive query.
+const RETRIES: u32 = 3;
+/// The time to wait before retrying a failed archive query.
+const RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Try an operation on the archive, retrying up to `retries` times after waiting `delay` if it
+/// fails, since some errors, like a busy database, are transient.
+///
+/// The error from the last attempt is returned if all the attempts fail.
+fn with_retry<T>(
+    retries: u32,
+    delay: std::time::Duration,
+    op: impl Fn() -> Result<T, BufkitDataErr>,
+) -> Result<T, BufkitDataErr> {
+    let mut attempt = 0;
+    loop {
+        match op() {
+            Ok(val) => return Ok(val),
+            Err(err) if attempt >= retries => return Err(err),
+            Err(_) => {
+                attempt += 1;
+                sleep(delay);
+            }
+        }
+    }
+}
+
 /// The number of days of data available for each model.
-fn num_days(model: Model) -> i64 {
+fn num_days(model: Model, horizons: &ForecastHorizons) -> i64 {
     match model {
-        Model::GFS => 7,
-        Model::NAM => 4,
-        Model::NAM4KM => 3,
+        Model::GFS => horizons.gfs,
+        Model::NAM => horizons.nam,
+        Model::NAM4KM => horizons.nam4km,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn time(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2020, 8, day)
+            .and_then(|date| date.and_hms_opt(hour, 0, 0))
+            .unwrap()
+    }
+
+    #[test]
+    fn cache_directory_is_shared_and_pruned() {
+        let dir = std::env::temp_dir().join(format!("fwx_cache_test_{}", std::process::id()));
+        let station_num = StationNumber::from(727_730);
+
+        let cache = BufkitStringCache::with_directory(&dir).unwrap();
+        cache.insert(station_num, Model::GFS, time(1, 0), "old run".to_owned());
+        cache.insert(station_num, Model::GFS, time(2, 0), "new run".to_owned());
+
+        // A new cache, e.g. in the next cron job, finds the runs saved by the first one.
+        let reopened = BufkitStringCache::with_directory(&dir).unwrap();
+        assert!(reopened.is_empty());
+        assert_eq!(
+            reopened.get(station_num, Model::GFS, time(2, 0)).as_deref(),
+            Some("new run")
+        );
+        assert_eq!(reopened.len(), 1);
+
+        reopened.prune(Model::GFS, time(1, 12));
+        let pruned = BufkitStringCache::with_directory(&dir).unwrap();
+        assert!(pruned.get(station_num, Model::GFS, time(1, 0)).is_none());
+        assert!(pruned.get(station_num, Model::GFS, time(2, 0)).is_some());
+
+        pruned.clear();
+        assert!(BufkitStringCache::with_directory(&dir)
+            .unwrap()
+            .get(station_num, Model::GFS, time(2, 0))
+            .is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
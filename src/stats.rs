@@ -0,0 +1,63 @@
+//! Statistics calculated directly from the model data, as opposed to the climatology database.
+
+/// Calculate the `q`-th quantile of the values, where `q` is from 0.0 to 1.0.
+///
+/// NaN values are skipped, and the quantile is linearly interpolated between the two nearest
+/// values when it falls between them. If there are no values that aren't NaN, or `q` is out of
+/// range, the result is NaN.
+pub fn quantile(values: &[f64], q: f64) -> f64 {
+    if !(0.0..=1.0).contains(&q) {
+        return std::f64::NAN;
+    }
+
+    let mut vals: Vec<f64> = values.iter().cloned().filter(|val| !val.is_nan()).collect();
+    if vals.is_empty() {
+        return std::f64::NAN;
+    }
+    vals.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let pos = q * (vals.len() - 1) as f64;
+    let lower = pos.floor() as usize;
+    let upper = pos.ceil() as usize;
+    let frac = pos - lower as f64;
+
+    vals[lower] + (vals[upper] - vals[lower]) * frac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantile_interpolates_between_ranks() {
+        let values = [4.0, 1.0, 3.0, 2.0];
+
+        assert_eq!(quantile(&values, 0.5), 2.5);
+        assert_eq!(quantile(&values, 0.25), 1.75);
+        assert_eq!(quantile(&values, 2.0 / 3.0), 3.0);
+    }
+
+    #[test]
+    fn quantile_end_points_are_min_and_max() {
+        let values = [4.0, 1.0, 3.0, 2.0];
+
+        assert_eq!(quantile(&values, 0.0), 1.0);
+        assert_eq!(quantile(&values, 1.0), 4.0);
+    }
+
+    #[test]
+    fn quantile_skips_nan() {
+        assert_eq!(quantile(&[std::f64::NAN, 3.0, 1.0], 0.5), 2.0);
+        assert!(quantile(&[std::f64::NAN, std::f64::NAN], 0.5).is_nan());
+        assert!(quantile(&[], 0.5).is_nan());
+    }
+
+    #[test]
+    fn quantile_out_of_range() {
+        let values = [4.0, 1.0, 3.0, 2.0];
+
+        assert!(quantile(&values, -0.1).is_nan());
+        assert!(quantile(&values, 1.1).is_nan());
+        assert!(quantile(&values, std::f64::NAN).is_nan());
+    }
+}
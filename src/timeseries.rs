@@ -1,11 +1,24 @@
-use bufkit_data::SiteInfo;
-use chrono::{Duration, NaiveDateTime};
-use std::collections::hash_map::{Entry, HashMap};
+use crate::{constants::GP_DATE_FORMAT, error::FwxError};
+use bufkit_data::{SiteInfo, StationNumber};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{
+        hash_map::{Entry, HashMap},
+        BTreeMap, HashSet,
+    },
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
 
 /// `MetaData` contains information about when the associated data should start and stop, what time
 /// is considered now, the site, and the model name for which the associated data is valid for.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MetaData {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::site_info"))]
     pub site: SiteInfo,
     pub model: String,
     pub start: NaiveDateTime,
@@ -26,16 +39,49 @@ pub trait ValidTime {
 pub trait ModelTimes: ValidTime {
     /// Get the lead time
     fn lead_time(&self) -> Option<Duration>;
+
+    /// Get the lead time in whole hours.
+    fn lead_time_hours(&self) -> Option<i64> {
+        self.lead_time().map(|lt| lt.num_hours())
+    }
+
+    /// Get the lead time in whole minutes.
+    fn lead_time_minutes(&self) -> Option<i64> {
+        self.lead_time().map(|lt| lt.num_minutes())
+    }
+
+    /// Get the lead time in whole seconds.
+    fn lead_time_seconds(&self) -> Option<i64> {
+        self.lead_time().map(|lt| lt.num_seconds())
+    }
+}
+
+/// `Interpolatable` is a trait for objects that can be linearly interpolated in time, which is
+/// needed to regularize the time steps in a `TimeSeries`.
+pub trait Interpolatable: ValidTime {
+    /// Interpolate a value that is the fraction `frac` of the way from `a` to `b`.
+    fn interpolate(a: &Self, b: &Self, frac: f64) -> Self;
+
+    /// Create a value marking missing data at `valid_time`. The `nearest` value is provided so
+    /// any fields that are not data, like a lead time, can be derived from it.
+    fn missing(nearest: &Self, valid_time: NaiveDateTime) -> Self;
 }
 
 /// `TimeSeries` is a wrapper around a `std::vec::Vec` with elements that are sorted by their
 ///  valid times.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct TimeSeries<T: ValidTime> {
     pub data: Vec<T>,
 }
 
 /// `EnsembleList` contains a `MetaData` and a list of data items each associated with an
 /// initialization time, i.e. a model initialization time.
+///
+/// It is `Send` and `Sync` whenever `T` is, so an `EnsembleSeries<AnalyzedData>` can be shared
+/// between threads.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EnsembleList<T> {
     pub meta: MetaData,
     pub data: Vec<(NaiveDateTime, T)>,
@@ -47,28 +93,127 @@ pub type EnsembleSeries<T> = EnsembleList<TimeSeries<T>>;
 
 /// `MergedSeries` contains a `MetaData` and a `TimeSeries`. It may represent a single model run
 /// or an ensemble of model runs with different initialization times merged into a single time
-/// series where for any valid time the ensemble member with the shortest lead time selected for
-/// the time series.
+/// series where for any valid time the ensemble member with the shortest lead time, or chosen by
+/// some other strategy, is selected for the time series.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MergedSeries<T: ValidTime> {
     pub meta: MetaData,
     pub data: TimeSeries<T>,
 }
 
+impl MetaData {
+    /// Check if the time range from `start` to `end` overlaps with another `MetaData`, inclusive.
+    pub fn overlaps(&self, other: &MetaData) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    /// Check if a time is in the range from `start` to `end`, inclusive.
+    pub fn contains_time(&self, t: NaiveDateTime) -> bool {
+        t >= self.start && t <= self.end
+    }
+
+    /// The length of the time range from `start` to `end`.
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// Make a copy with a different `now`, e.g. to see a case as it looked at an earlier time.
+    pub fn with_now(&self, new_now: NaiveDateTime) -> MetaData {
+        MetaData {
+            now: new_now,
+            ..self.clone()
+        }
+    }
+
+    /// Make a copy with a different time range from `start` to `end`.
+    pub fn with_time_window(&self, start: NaiveDateTime, end: NaiveDateTime) -> MetaData {
+        MetaData {
+            start,
+            end,
+            ..self.clone()
+        }
+    }
+}
+
+// The site is compared in full by `PartialEq`, but only the station number is hashed. Equal values
+// still have equal hashes, which is all `HashMap` needs.
+impl Eq for MetaData {}
+
+impl Hash for MetaData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.site.station_num.hash(state);
+        self.model.hash(state);
+        self.start.hash(state);
+        self.now.hash(state);
+        self.end.hash(state);
+    }
+}
+
+impl Display for MetaData {
+    /// A compact summary, e.g. `Site:KTUS(727730) Model:GFS 2023-08-01-00..2023-08-08-00
+    /// (now:2023-08-04-12)`.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.site.name.as_ref() {
+            Some(name) => write!(f, "Site:{}({})", name, self.site.station_num)?,
+            None => write!(f, "Site:{}", self.site.station_num)?,
+        }
+
+        write!(
+            f,
+            " Model:{} {}..{} (now:{})",
+            self.model,
+            self.start.format(GP_DATE_FORMAT),
+            self.end.format(GP_DATE_FORMAT),
+            self.now.format(GP_DATE_FORMAT)
+        )
+    }
+}
+
 impl<T> EnsembleList<T> {
     /// Map and filter out errors.
     pub fn filter_map<U, F>(&self, func: F) -> EnsembleList<U>
     where
         F: Fn(&T) -> Option<U>,
     {
-        let EnsembleList { meta, data } = &self;
+        let data: Vec<(NaiveDateTime, U)> = self
+            .into_iter()
+            .filter_map(|(init_time, t)| func(t).map(|u| (*init_time, u)))
+            .collect();
+
+        EnsembleList {
+            meta: self.meta.clone(),
+            data,
+        }
+    }
+
+    /// Transform each item, keeping the initialization times and `MetaData`.
+    pub fn map<U, F>(self, func: F) -> EnsembleList<U>
+    where
+        F: Fn(T) -> U,
+    {
+        let EnsembleList { meta, data } = self;
 
         let data: Vec<(NaiveDateTime, U)> = data
-            .iter()
-            .filter_map(|(init_time, t)| func(t).map(|u| (*init_time, u)))
+            .into_iter()
+            .map(|(init_time, t)| (init_time, func(t)))
+            .collect();
+
+        EnsembleList { meta, data }
+    }
+
+    /// Same as `map`, but borrows this list instead of consuming it.
+    pub fn map_ref<U, F>(&self, func: F) -> EnsembleList<U>
+    where
+        F: Fn(&T) -> U,
+    {
+        let data: Vec<(NaiveDateTime, U)> = self
+            .into_iter()
+            .map(|(init_time, t)| (*init_time, func(t)))
             .collect();
 
         EnsembleList {
-            meta: meta.clone(),
+            meta: self.meta.clone(),
             data,
         }
     }
@@ -77,9 +222,177 @@ impl<T> EnsembleList<T> {
     pub fn is_empty(&self) -> bool {
         self.data.is_empty()
     }
+
+    /// The number of items in the list, e.g. the number of model runs in an `EnsembleSeries`.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Sort the items by ascending initialization time, as `EnsembleSeries::merge` expects.
+    ///
+    /// The sort is stable, so items with the same initialization time stay in the same order.
+    pub fn sort_by_init_time(&mut self) {
+        self.data.sort_by_key(|(init_time, _)| *init_time);
+    }
+
+    /// Check if the items are sorted by ascending initialization time.
+    pub fn is_sorted_by_init_time(&self) -> bool {
+        self.data.windows(2).all(|pair| pair[0].0 <= pair[1].0)
+    }
+
+    /// Keep this list only if it is for the site with the given station number.
+    pub fn filter_by_site(self, site_num: StationNumber) -> Option<Self> {
+        if self.meta.site.station_num == site_num {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    /// Keep this list only if it is for the given model, compared case-insensitively.
+    pub fn filter_by_model(self, model: &str) -> Option<Self> {
+        if self.meta.model.eq_ignore_ascii_case(model) {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    /// Keep this list only if it is for the given site and model.
+    pub fn filter_by(self, site_num: StationNumber, model: &str) -> Option<Self> {
+        self.filter_by_site(site_num)?.filter_by_model(model)
+    }
+
+    /// Remove items with duplicate initialization times, keeping the one with the largest
+    /// `size_fn`, or the last one if they are the same size.
+    ///
+    /// This cleans up after an archive was updated while loading, which can produce a partial
+    /// and a complete copy of the same model run. The items stay in their original order.
+    pub fn deduplicate_by_init_time_with<F>(self, size_fn: F) -> Self
+    where
+        F: Fn(&T) -> usize,
+    {
+        let EnsembleList { meta, data } = self;
+
+        let mut deduped: Vec<(NaiveDateTime, T)> = Vec::with_capacity(data.len());
+        let mut indexes: HashMap<NaiveDateTime, usize> = HashMap::new();
+        for (init_time, t) in data {
+            match indexes.entry(init_time) {
+                Entry::Occupied(entry) => {
+                    let kept = &mut deduped[*entry.get()];
+                    if size_fn(&t) >= size_fn(&kept.1) {
+                        *kept = (init_time, t);
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(deduped.len());
+                    deduped.push((init_time, t));
+                }
+            }
+        }
+
+        EnsembleList {
+            meta,
+            data: deduped,
+        }
+    }
 }
 
 impl<T: ValidTime> EnsembleSeries<T> {
+    /// Get the model run with the given initialization time, if it is in this ensemble.
+    pub fn get_run(&self, init_time: NaiveDateTime) -> Option<&TimeSeries<T>> {
+        self.data
+            .iter()
+            .find(|(it, _)| *it == init_time)
+            .map(|(_, time_series)| time_series)
+    }
+
+    /// Get the initialization times of all the model runs in this ensemble, sorted in ascending
+    /// order.
+    pub fn init_times(&self) -> Vec<NaiveDateTime> {
+        let mut init_times: Vec<NaiveDateTime> =
+            self.data.iter().map(|(init_time, _)| *init_time).collect();
+        init_times.sort();
+
+        init_times
+    }
+
+    /// The total number of time steps in all the model runs.
+    pub fn total_time_steps(&self) -> usize {
+        self.data
+            .iter()
+            .map(|(_init_time, time_series)| time_series.len())
+            .sum()
+    }
+
+    /// Add a model run to the ensemble, keeping the runs sorted by ascending initialization
+    /// time.
+    ///
+    /// The `data` is sorted first in case it was modified directly. If there is already a run with
+    /// the same initialization time, it is always replaced by the new run, even if the new run is
+    /// shorter. This is unlike `deduplicate_by_init_time`, which keeps the longest run, because an
+    /// explicitly added run is assumed to be the one wanted.
+    pub fn add_run(&mut self, init_time: NaiveDateTime, series: TimeSeries<T>) {
+        self.sort_by_init_time();
+
+        match self.data.binary_search_by_key(&init_time, |(it, _)| *it) {
+            Ok(idx) => self.data[idx] = (init_time, series),
+            Err(idx) => self.data.insert(idx, (init_time, series)),
+        }
+    }
+
+    /// Remove model runs with duplicate initialization times, keeping the longest, or the last one
+    /// if they are the same length. See `deduplicate_by_init_time_with`.
+    pub fn deduplicate_by_init_time(self) -> Self {
+        self.deduplicate_by_init_time_with(TimeSeries::len)
+    }
+
+    /// Split the ensemble into groups of model runs initialized on the same calendar day.
+    ///
+    /// The groups are sorted by day, and the `now` member of the `MetaData` for each group is set
+    /// to noon on that day.
+    pub fn split_by_init_day(&self) -> Vec<Self>
+    where
+        T: Clone,
+    {
+        let mut by_day: BTreeMap<NaiveDate, Vec<(NaiveDateTime, TimeSeries<T>)>> = BTreeMap::new();
+        for (init_time, time_series) in self.data.iter() {
+            by_day
+                .entry(init_time.date())
+                .or_default()
+                .push((*init_time, time_series.clone()));
+        }
+
+        by_day
+            .into_iter()
+            .map(|(day, data)| {
+                let mut meta = self.meta.clone();
+                meta.now = day.and_hms_opt(12, 0, 0).unwrap();
+
+                EnsembleSeries { meta, data }
+            })
+            .collect()
+    }
+
+    /// Trim every model run to the valid times from `start` to `end`, inclusive, and update the
+    /// `MetaData` to match.
+    ///
+    /// Any model runs with no values left after trimming are removed.
+    pub fn filter_valid_times(self, start: NaiveDateTime, end: NaiveDateTime) -> Self {
+        let EnsembleSeries { mut meta, data } = self;
+
+        let data: Vec<(NaiveDateTime, TimeSeries<T>)> = data
+            .into_iter()
+            .map(|(init_time, time_series)| (init_time, time_series.into_trim(start, end)))
+            .filter(|(_init_time, time_series)| !time_series.is_empty())
+            .collect();
+
+        meta.start = start;
+        meta.end = end;
+
+        EnsembleSeries { meta, data }
+    }
+
     /// Map and filter out errors.
     pub fn filter_map_inner<U, F>(&self, func: F) -> EnsembleSeries<U>
     where
@@ -114,6 +427,115 @@ impl<T: ValidTime> EnsembleSeries<T> {
     }
 }
 
+/// A builder for assembling an `EnsembleSeries` one model run at a time, for example to combine
+/// model runs from different sources.
+pub struct EnsembleSeriesBuilder<T: ValidTime> {
+    site: Option<SiteInfo>,
+    model: Option<String>,
+    start: Option<NaiveDateTime>,
+    now: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+    data: Vec<(NaiveDateTime, TimeSeries<T>)>,
+}
+
+impl<T: ValidTime> Default for EnsembleSeriesBuilder<T> {
+    fn default() -> Self {
+        EnsembleSeriesBuilder {
+            site: None,
+            model: None,
+            start: None,
+            now: None,
+            end: None,
+            data: vec![],
+        }
+    }
+}
+
+impl<T: ValidTime> EnsembleSeriesBuilder<T> {
+    /// Create a new, empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the site.
+    pub fn site(mut self, site: SiteInfo) -> Self {
+        self.site = Some(site);
+        self
+    }
+
+    /// Set the model name.
+    pub fn model(mut self, model: &str) -> Self {
+        self.model = Some(model.to_owned());
+        self
+    }
+
+    /// Set the start time, defaults to the earliest valid time in the model runs.
+    pub fn start(mut self, start: NaiveDateTime) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Set the time considered now, defaults to the start time.
+    pub fn now(mut self, now: NaiveDateTime) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Set the end time, defaults to the latest valid time in the model runs.
+    pub fn end(mut self, end: NaiveDateTime) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Add a model run, see `EnsembleSeries::add_run`.
+    pub fn add_run(mut self, init_time: NaiveDateTime, series: TimeSeries<T>) -> Self {
+        self.data.push((init_time, series));
+        self
+    }
+
+    /// Build the `EnsembleSeries`.
+    ///
+    /// Returns `None` if the site or model were not set, or if the start or end time were not set
+    /// and there are no valid times in the model runs to derive them from.
+    pub fn build(self) -> Option<EnsembleSeries<T>> {
+        let EnsembleSeriesBuilder {
+            site,
+            model,
+            start,
+            now,
+            end,
+            data,
+        } = self;
+
+        let valid_times = || {
+            data.iter()
+                .flat_map(|(_, time_series)| time_series.iter())
+                .filter_map(|val| val.valid_time())
+        };
+
+        let start = start.or_else(|| valid_times().min())?;
+        let end = end.or_else(|| valid_times().max())?;
+        let now = now.unwrap_or(start);
+
+        let mut ens = EnsembleSeries {
+            meta: MetaData {
+                site: site?,
+                model: model?,
+                start,
+                now,
+                end,
+            },
+            data: vec![],
+        };
+
+        for (init_time, series) in data {
+            ens.add_run(init_time, series);
+        }
+
+        Some(ens)
+    }
+}
+
 impl<T: ValidTime> MergedSeries<T> {
     /// Map and filter out errors.
     pub fn filter_map<U, F>(&self, func: F) -> MergedSeries<U>
@@ -142,17 +564,318 @@ impl<T: ValidTime> MergedSeries<T> {
     }
 }
 
+impl<T: Interpolatable + Clone> TimeSeries<T> {
+    /// Create a new `TimeSeries` with values every `step` from the first to the last valid time
+    /// in this series.
+    ///
+    /// Gaps of up to two time steps are filled by linear interpolation, larger gaps are filled
+    /// with missing values. The last valid time is always included, even if it is not a whole
+    /// number of steps from the first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is not positive.
+    pub fn resample(&self, step: Duration) -> Self {
+        let times = self.data.iter().filter_map(|val| val.valid_time());
+        match (times.clone().min(), times.max()) {
+            (Some(start), Some(end)) => self.resample_in(start, end, step, step * 2),
+            _ => TimeSeries { data: vec![] },
+        }
+    }
+
+    /// Create a new `TimeSeries` with values every `step` from `start` to `end`, inclusive.
+    ///
+    /// Gaps of up to `max_gap` are filled by linear interpolation, larger gaps and any times
+    /// before or after the available data are filled with missing values. The result always ends
+    /// with a value at `end`, even if it is not a whole number of steps from `start`. If this
+    /// series is empty or `end` is before `start`, the result is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is not positive.
+    pub fn resample_in(
+        &self,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        step: Duration,
+        max_gap: Duration,
+    ) -> Self {
+        assert!(step > Duration::zero(), "resample step must be positive");
+
+        let vals: Vec<(NaiveDateTime, &T)> = self
+            .data
+            .iter()
+            .filter_map(|val| val.valid_time().map(|vt| (vt, val)))
+            .collect();
+
+        if vals.is_empty() {
+            return TimeSeries { data: vec![] };
+        }
+
+        let mut times: Vec<NaiveDateTime> = vec![];
+        let mut curr_time = start;
+        while curr_time < end {
+            times.push(curr_time);
+            curr_time += step;
+        }
+        if start <= end {
+            times.push(end);
+        }
+
+        let mut data: Vec<T> = Vec::with_capacity(times.len());
+        let mut idx = 0;
+        for curr_time in times {
+            // Move to the first value not before the current time
+            while idx < vals.len() && vals[idx].0 < curr_time {
+                idx += 1;
+            }
+
+            let val = if idx < vals.len() && vals[idx].0 == curr_time {
+                vals[idx].1.clone()
+            } else if idx == 0 {
+                T::missing(vals[0].1, curr_time)
+            } else if idx == vals.len() {
+                T::missing(vals[idx - 1].1, curr_time)
+            } else {
+                let (a_time, a) = vals[idx - 1];
+                let (b_time, b) = vals[idx];
+                let gap = b_time - a_time;
+
+                if gap <= max_gap {
+                    let frac = (curr_time - a_time).num_seconds() as f64 / gap.num_seconds() as f64;
+                    T::interpolate(a, b, frac)
+                } else if curr_time - a_time <= b_time - curr_time {
+                    T::missing(a, curr_time)
+                } else {
+                    T::missing(b, curr_time)
+                }
+            };
+
+            data.push(val);
+        }
+
+        TimeSeries { data }
+    }
+}
+
+impl<T: Interpolatable + Clone> MergedSeries<T> {
+    /// Create a new `MergedSeries` with values every `step` from the start to the end time in the
+    /// `MetaData`.
+    ///
+    /// Gaps of up to two time steps are filled by linear interpolation, larger gaps are filled
+    /// with missing values. The start and end times are always included, see
+    /// `TimeSeries::resample_in`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is not positive.
+    pub fn resample(&self, step: Duration) -> Self {
+        let MergedSeries { meta, data } = self;
+
+        MergedSeries {
+            meta: meta.clone(),
+            data: data.resample_in(meta.start, meta.end, step, step * 2),
+        }
+    }
+}
+
+impl<T: ValidTime> TimeSeries<T> {
+    /// Get an iterator over references to the values in the series.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Get an iterator over mutable references to the values in the series.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.data.iter_mut()
+    }
+
+    /// Get an iterator over all the overlapping runs of `size` consecutive values, see
+    /// `slice::windows`.
+    ///
+    /// The windows are by count, not time, so gaps in the series are not accounted for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn windows(&self, size: usize) -> std::slice::Windows<'_, T> {
+        self.data.windows(size)
+    }
+
+    /// Get an iterator over the series in non-overlapping runs of `size` consecutive values, the
+    /// last one may be shorter, see `slice::chunks`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn chunks(&self, size: usize) -> std::slice::Chunks<'_, T> {
+        self.data.chunks(size)
+    }
+
+    /// The number of values in the series.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Check if the series is empty.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Create a new `TimeSeries` with only the values valid from `start` to `end`, inclusive.
+    ///
+    /// If no values are in that range, the result is empty.
+    pub fn trim(&self, start: NaiveDateTime, end: NaiveDateTime) -> Self
+    where
+        T: Clone,
+    {
+        let data: Vec<T> = self
+            .data
+            .iter()
+            .filter(|val| in_range(val.valid_time(), start, end))
+            .cloned()
+            .collect();
+
+        TimeSeries { data }
+    }
+
+    /// Same as `trim`, but consumes this series instead of cloning the values.
+    pub fn into_trim(self, start: NaiveDateTime, end: NaiveDateTime) -> Self {
+        let TimeSeries { mut data } = self;
+        data.retain(|val| in_range(val.valid_time(), start, end));
+
+        TimeSeries { data }
+    }
+
+    /// Split the series into contiguous pieces wherever consecutive valid times are more than
+    /// `max_gap` apart.
+    ///
+    /// Values without a valid time stay with the piece they are in. The result is never empty, if
+    /// there are no gaps it contains a copy of this series as the only element.
+    pub fn split_at_gap(&self, max_gap: Duration) -> Vec<Self>
+    where
+        T: Clone,
+    {
+        let mut pieces: Vec<Self> = vec![];
+        let mut current: Vec<T> = vec![];
+        let mut last_time: Option<NaiveDateTime> = None;
+
+        for val in self.data.iter() {
+            if let Some(vt) = val.valid_time() {
+                if let Some(last) = last_time {
+                    if vt - last > max_gap && !current.is_empty() {
+                        pieces.push(TimeSeries {
+                            data: std::mem::take(&mut current),
+                        });
+                    }
+                }
+                last_time = Some(vt);
+            }
+
+            current.push(val.clone());
+        }
+
+        if !current.is_empty() || pieces.is_empty() {
+            pieces.push(TimeSeries { data: current });
+        }
+
+        pieces
+    }
+}
+
+/// Check if a valid time is in the range from `start` to `end`, inclusive.
+fn in_range(valid_time: Option<NaiveDateTime>, start: NaiveDateTime, end: NaiveDateTime) -> bool {
+    valid_time
+        .map(|vt| vt >= start && vt <= end)
+        .unwrap_or(false)
+}
+
 impl<T: ValidTime> AsRef<[T]> for TimeSeries<T> {
     fn as_ref(&self) -> &[T] {
         &self.data
     }
 }
 
+impl<T: ValidTime> IntoIterator for TimeSeries<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+/// Iterate over the items and their initialization times, the `MetaData` is dropped.
+impl<T> IntoIterator for EnsembleList<T> {
+    type Item = (NaiveDateTime, T);
+    type IntoIter = std::vec::IntoIter<(NaiveDateTime, T)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a EnsembleList<T> {
+    type Item = &'a (NaiveDateTime, T);
+    type IntoIter = std::slice::Iter<'a, (NaiveDateTime, T)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+impl<T: ValidTime> std::iter::FromIterator<T> for TimeSeries<T> {
+    /// Collect the values into a `TimeSeries`, sorting them by valid time.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut data: Vec<T> = iter.into_iter().collect();
+        data.sort_by_key(|val| val.valid_time());
+
+        TimeSeries { data }
+    }
+}
+
 impl<T: ModelTimes> EnsembleSeries<T> {
+    /// Remove any model runs whose first value has a lead time longer than `max_lead`.
+    ///
+    /// Empty model runs are removed too.
+    pub fn lead_time_filter(self, max_lead: Duration) -> EnsembleSeries<T> {
+        let EnsembleSeries { meta, data } = self;
+
+        let data: Vec<(NaiveDateTime, TimeSeries<T>)> = data
+            .into_iter()
+            .filter(|(_init_time, time_series)| {
+                time_series
+                    .as_ref()
+                    .first()
+                    .and_then(|val| val.lead_time())
+                    .map(|lead_time| lead_time <= max_lead)
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        EnsembleSeries { meta, data }
+    }
+
     /// Transform an `EnsembleSeries` into a `MergedSeries`.
     ///
-    /// Assumes the EnsembleSeries is sorted in order of ascending model initialization time.
+    /// For any valid time, the ensemble member with the shortest lead time is selected.
     pub fn merge(self) -> MergedSeries<T> {
+        self.merge_with_strategy(|a, b| a.lead_time().cmp(&b.lead_time()))
+    }
+
+    /// Transform an `EnsembleSeries` into a `MergedSeries` using `selector` to choose which
+    /// ensemble member to keep for each valid time.
+    ///
+    /// When two members have the same valid time, the one that `selector` orders first,
+    /// `Ordering::Less`, is kept. In the case of a tie, the member from the earlier model run is
+    /// kept. Assumes the EnsembleSeries is sorted in order of ascending model initialization
+    /// time, see `sort_by_init_time`.
+    pub fn merge_with_strategy<F>(self, selector: F) -> MergedSeries<T>
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        debug_assert!(self.is_sorted_by_init_time());
+
         let EnsembleSeries { meta, data } = self;
 
         let mut pool: HashMap<NaiveDateTime, T> = HashMap::new();
@@ -161,12 +884,11 @@ impl<T: ModelTimes> EnsembleSeries<T> {
             let TimeSeries { data: vec_t } = time_series_t;
 
             vec_t.into_iter().for_each(|val_t| {
-                if let (Some(valid_time), Some(lead_time)) = (val_t.valid_time(), val_t.lead_time())
-                {
+                if let (Some(valid_time), Some(_)) = (val_t.valid_time(), val_t.lead_time()) {
                     match pool.entry(valid_time) {
                         Entry::Occupied(mut entry) => {
                             let cmp_val = entry.get_mut();
-                            if lead_time < cmp_val.lead_time().unwrap() {
+                            if selector(&val_t, cmp_val) == Ordering::Less {
                                 *cmp_val = val_t;
                             }
                         }
@@ -185,3 +907,263 @@ impl<T: ModelTimes> EnsembleSeries<T> {
         MergedSeries { meta, data }
     }
 }
+
+/// Combine the ensembles for several models at the same site into one "grand ensemble" and merge
+/// it.
+///
+/// The `MetaData` of the result covers the widest time window of all the ensembles, uses the most
+/// recent `now`, and the model names are joined with a `+`, e.g. "GFS+NAM". For each valid time
+/// the member with the shortest lead time from any model is selected, see `merge`.
+///
+/// Returns an error if the ensembles are for different sites or there are none.
+pub fn merge_across_models<T: ModelTimes>(
+    series: Vec<EnsembleSeries<T>>,
+) -> Result<MergedSeries<T>, FwxError> {
+    let mut series = series.into_iter();
+    let EnsembleSeries { mut meta, mut data } = series.next().ok_or(FwxError::NoEnsembles)?;
+
+    for ens in series {
+        if ens.meta.site.station_num != meta.site.station_num {
+            return Err(FwxError::SiteMismatch {
+                expected: meta.site.description(),
+                found: ens.meta.site.description(),
+            });
+        }
+
+        meta.model = format!("{}+{}", meta.model, ens.meta.model);
+        meta.start = meta.start.min(ens.meta.start);
+        meta.now = meta.now.max(ens.meta.now);
+        meta.end = meta.end.max(ens.meta.end);
+        data.extend(ens.data);
+    }
+
+    // Merging assumes the members are sorted by initialization time, the sort is stable so runs
+    // from different models with the same initialization time stay in the given order.
+    let mut ens = EnsembleSeries { meta, data };
+    ens.sort_by_init_time();
+
+    Ok(ens.merge())
+}
+
+/// Concatenate two series, e.g. from consecutive runs of a model, into one sorted by valid time.
+///
+/// When both series have a value at the same valid time, the value from `b` is used, like the
+/// most recent run is used by `EnsembleSeries::merge`.
+pub fn concat<T: ValidTime>(a: TimeSeries<T>, b: TimeSeries<T>) -> TimeSeries<T> {
+    let b_times: HashSet<NaiveDateTime> = b.iter().filter_map(ValidTime::valid_time).collect();
+
+    let mut data: Vec<T> = a
+        .data
+        .into_iter()
+        .filter(|val| {
+            val.valid_time()
+                .map(|vt| !b_times.contains(&vt))
+                .unwrap_or(true)
+        })
+        .chain(b.data)
+        .collect();
+
+    // The sort is stable, so values without a valid time stay in order at the start.
+    data.sort_by_key(|val| val.valid_time());
+
+    TimeSeries { data }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Val {
+        valid_time: NaiveDateTime,
+        value: f64,
+    }
+
+    impl ValidTime for Val {
+        fn valid_time(&self) -> Option<NaiveDateTime> {
+            Some(self.valid_time)
+        }
+    }
+
+    fn time(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2020, 8, day)
+            .and_then(|date| date.and_hms_opt(hour, 0, 0))
+            .unwrap()
+    }
+
+    fn meta() -> MetaData {
+        MetaData {
+            site: SiteInfo {
+                station_num: StationNumber::from(727_730),
+                name: Some("kmso".to_owned()),
+                notes: None,
+                state: None,
+                auto_download: false,
+                time_zone: None,
+            },
+            model: "GFS".to_owned(),
+            start: time(1, 0),
+            now: time(1, 12),
+            end: time(2, 0),
+        }
+    }
+
+    fn series(init_time: NaiveDateTime, values: &[f64]) -> TimeSeries<Val> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| Val {
+                valid_time: init_time + Duration::hours(3 * i as i64),
+                value,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cloned_ensemble_series_equals_original() {
+        let ens = EnsembleSeries {
+            meta: meta(),
+            data: vec![
+                (time(1, 0), series(time(1, 0), &[1.0, 2.0, 3.0])),
+                (time(1, 6), series(time(1, 6), &[4.0, 5.0])),
+            ],
+        };
+
+        let cloned = ens.clone();
+
+        assert_eq!(cloned.meta, ens.meta);
+        assert_eq!(cloned.data.len(), ens.data.len());
+        for ((c_init, c_series), (o_init, o_series)) in cloned.data.iter().zip(ens.data.iter()) {
+            assert_eq!(c_init, o_init);
+            assert_eq!(c_series.data, o_series.data);
+        }
+    }
+
+    #[test]
+    fn cloned_merged_series_equals_original() {
+        let mg = MergedSeries {
+            meta: meta(),
+            data: series(time(1, 0), &[1.0, std::f64::NAN, 3.0]),
+        };
+
+        let cloned = mg.clone();
+
+        assert_eq!(cloned.meta, mg.meta);
+        assert_eq!(cloned.data.len(), mg.data.len());
+        for (c_val, o_val) in cloned.data.iter().zip(mg.data.iter()) {
+            assert_eq!(c_val.valid_time, o_val.valid_time);
+            assert_eq!(c_val.value.to_bits(), o_val.value.to_bits());
+        }
+    }
+
+    /// A value from a model run, labeled with the model so the merge can be checked.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Member {
+        valid_time: NaiveDateTime,
+        lead_time: Duration,
+        model: &'static str,
+    }
+
+    impl ValidTime for Member {
+        fn valid_time(&self) -> Option<NaiveDateTime> {
+            Some(self.valid_time)
+        }
+    }
+
+    impl ModelTimes for Member {
+        fn lead_time(&self) -> Option<Duration> {
+            Some(self.lead_time)
+        }
+    }
+
+    /// A model run with a value every 6 hours for a day.
+    fn run(model: &'static str, init_time: NaiveDateTime) -> (NaiveDateTime, TimeSeries<Member>) {
+        let data = (0..5)
+            .map(|i| Member {
+                valid_time: init_time + Duration::hours(6 * i),
+                lead_time: Duration::hours(6 * i),
+                model,
+            })
+            .collect();
+
+        (init_time, data)
+    }
+
+    fn ensemble(
+        model: &str,
+        runs: Vec<(NaiveDateTime, TimeSeries<Member>)>,
+    ) -> EnsembleSeries<Member> {
+        EnsembleSeries {
+            meta: MetaData {
+                model: model.to_owned(),
+                ..meta()
+            },
+            data: runs,
+        }
+    }
+
+    #[test]
+    fn merge_across_models_requires_ensembles() {
+        assert!(matches!(
+            merge_across_models::<Member>(vec![]),
+            Err(FwxError::NoEnsembles)
+        ));
+    }
+
+    #[test]
+    fn merge_across_models_requires_the_same_site() {
+        let gfs = ensemble("GFS", vec![run("GFS", time(1, 0))]);
+        let mut nam = ensemble("NAM", vec![run("NAM", time(1, 0))]);
+        nam.meta.site.station_num = StationNumber::from(727_745);
+
+        assert!(matches!(
+            merge_across_models(vec![gfs, nam]),
+            Err(FwxError::SiteMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn merge_across_models_combines_the_meta_data() {
+        let gfs = ensemble("GFS", vec![run("GFS", time(1, 0))]);
+        let mut nam = ensemble("NAM", vec![run("NAM", time(1, 6))]);
+        nam.meta.start = time(1, 6);
+        nam.meta.now = time(1, 18);
+        nam.meta.end = time(3, 0);
+
+        let merged = merge_across_models(vec![gfs, nam]).unwrap();
+
+        assert_eq!(merged.meta.model, "GFS+NAM");
+        assert_eq!(merged.meta.start, time(1, 0));
+        assert_eq!(merged.meta.now, time(1, 18));
+        assert_eq!(merged.meta.end, time(3, 0));
+    }
+
+    #[test]
+    fn merge_across_models_keeps_the_shortest_lead_time() {
+        let gfs = ensemble("GFS", vec![run("GFS", time(1, 0))]);
+        let nam = ensemble("NAM", vec![run("NAM", time(1, 6)), run("NAM", time(1, 12))]);
+        // Same initialization time as the first NAM run, so the lead times tie.
+        let rap = ensemble("RAP", vec![run("RAP", time(1, 6))]);
+
+        let merged = merge_across_models(vec![gfs, nam, rap]).unwrap();
+
+        let models: Vec<(NaiveDateTime, &str)> = merged
+            .data
+            .iter()
+            .map(|member| (member.valid_time, member.model))
+            .collect();
+        assert_eq!(
+            models,
+            vec![
+                (time(1, 0), "GFS"),
+                // Ties are kept from the ensemble given first.
+                (time(1, 6), "NAM"),
+                (time(1, 12), "NAM"),
+                (time(1, 18), "NAM"),
+                (time(2, 0), "NAM"),
+                (time(2, 6), "NAM"),
+                (time(2, 12), "NAM"),
+            ]
+        );
+    }
+}
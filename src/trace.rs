@@ -0,0 +1,37 @@
+//! Macros for emitting `tracing` events when the `tracing` feature is enabled.
+//!
+//! Without the feature, errors are printed to stderr and everything else compiles to nothing.
+
+/// Report an error.
+macro_rules! trace_error {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::error!($($arg)*);
+        #[cfg(not(feature = "tracing"))]
+        eprintln!("Error: {}", format_args!($($arg)*));
+    };
+}
+
+/// Report a potential problem.
+macro_rules! trace_warn {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::warn!($($arg)*);
+        #[cfg(not(feature = "tracing"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+/// Report information useful for debugging.
+macro_rules! trace_debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+        #[cfg(not(feature = "tracing"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
@@ -4,11 +4,24 @@
 use crate::timeseries::{ModelTimes, TimeSeries, ValidTime};
 use chrono::{Duration, NaiveDateTime};
 
+use bufkit_data::BufkitDataErr;
 use sounding_analysis::Sounding;
 use sounding_bufkit::BufkitData;
 
 mod analyzed_data;
-pub use analyzed_data::AnalyzedData;
+pub use analyzed_data::{AnalysisConfig, AnalyzedData};
+
+mod merge_strategy;
+pub use merge_strategy::MergeStrategy;
+
+mod records;
+pub use records::AnalyzedRecord;
+
+mod statistics;
+pub use statistics::{EnsembleFieldStats, SpreadStats, TimeStepStats};
+
+mod thresholds;
+pub use thresholds::{check_thresholds, ThresholdExceedance};
 
 impl ValidTime for Sounding {
     fn valid_time(&self) -> Option<NaiveDateTime> {
@@ -28,20 +41,8 @@ pub fn parse_sounding(
     start: NaiveDateTime,
     end: NaiveDateTime,
 ) -> Option<TimeSeries<Sounding>> {
-    BufkitData::init(&str_data, "")
+    parse_soundings_from_str(str_data, start, end)
         .ok()
-        .map(|data| {
-            data.into_iter()
-                .filter(|(snd, _)| {
-                    if let Some(vtime) = snd.valid_time() {
-                        vtime >= start && vtime <= end
-                    } else {
-                        false
-                    }
-                })
-                .map(|(snd, _)| snd)
-                .collect::<Vec<Sounding>>()
-        })
         .and_then(|vec_anals| {
             if vec_anals.is_empty() {
                 None
@@ -50,3 +51,29 @@ pub fn parse_sounding(
             }
         })
 }
+
+/// Parse the text of a bufkit file into soundings with valid times from `start` to `end`,
+/// inclusive.
+///
+/// The returned soundings are sorted by valid time. Soundings without a valid time are skipped.
+pub fn parse_soundings_from_str(
+    input: &str,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Result<Vec<Sounding>, BufkitDataErr> {
+    let mut soundings: Vec<Sounding> = BufkitData::init(input, "")?
+        .into_iter()
+        .map(|(snd, _)| snd)
+        .filter(|snd| {
+            if let Some(vtime) = snd.valid_time() {
+                vtime >= start && vtime <= end
+            } else {
+                false
+            }
+        })
+        .collect();
+
+    soundings.sort_by_key(|snd| snd.valid_time());
+
+    Ok(soundings)
+}
@@ -1,18 +1,144 @@
-use crate::timeseries::{ModelTimes, ValidTime};
+use crate::{
+    constants::GP_DATE_FORMAT,
+    timeseries::{Interpolatable, ModelTimes, ValidTime},
+};
 use chrono::{Duration, NaiveDateTime};
 
-use metfor::{CelsiusDiff, Meters};
-use sounding_analysis::{experimental::fire::blow_up, hot_dry_windy, Sounding};
+use itertools::izip;
+use metfor::{
+    potential_temperature, rh, Celsius, CelsiusDiff, Knots, Meters, MetersPSec, Quantity,
+    WindSpdDir,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sounding_analysis::{experimental::fire::blow_up, haines, hot_dry_windy, Sounding};
+use std::fmt::Display;
 
-#[derive(Debug)]
+/// Configuration options for the analysis performed by `AnalyzedData::analyze_with_config`.
+#[derive(Clone, Copy, Debug)]
+pub struct AnalysisConfig {
+    /// Blow up heights less than this value are considered insignificant and are not plotted.
+    pub min_blowup: Meters,
+    /// Check the quality of the analyzed data with `validate` and report any warnings before
+    /// plotting or saving it. The warnings are passed to the error handler if there is one, and
+    /// printed to stderr otherwise.
+    pub validate: bool,
+    /// Write the climatology for every `ClimoElement` to the merged plots, as `$climo_hdw`,
+    /// `$climo_blowupdt`, etc., and not only the HDW climatology used by the built in script. This
+    /// is for custom scripts that plot the climatology of other parameters.
+    pub all_climo_elements: bool,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        AnalysisConfig {
+            min_blowup: Meters(2000.0),
+            validate: false,
+            all_climo_elements: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnalyzedData {
     pub valid_time: NaiveDateTime,
     pub lead_time: i32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
     pub hdw: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub ffwi: f64,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialize::quantity_nan_as_null")
+    )]
     pub blow_up_dt: CelsiusDiff,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialize::quantity_nan_as_null")
+    )]
     pub blow_up_height: Meters,
+    /// The Haines Index, 2-6, or 0 if it could not be calculated.
+    pub haines_index: u8,
+    /// The mixing height above ground level.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialize::quantity_nan_as_null")
+    )]
+    pub mixing_height: Meters,
+    /// The relative humidity at the surface in percent, 0-100.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub surface_rh: f64,
+    /// The temperature at the surface in Celsius.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub surface_temperature_c: f64,
+    /// The wind speed at the surface (10 m) in meters per second.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub surface_wind_speed_ms: f64,
+    /// The direction the surface (10 m) wind is blowing from in degrees, 0-360.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub surface_wind_direction_deg: f64,
+    /// The mean wind speed through the mixing layer in meters per second.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub transport_wind_speed_ms: f64,
+    /// The direction the mean wind through the mixing layer is blowing from in degrees, 0-360.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub transport_wind_dir_deg: f64,
 }
 
+/// A numeric field, its name, the function to get the value, and whether it is a floating point
+/// value that may be NaN.
+type Field = (&'static str, fn(&AnalyzedData) -> f64, bool);
+
+/// The numeric fields in the order returned by `to_f64_vec`. Update this when adding a field, new
+/// fields are only ever added at the end.
+const FIELDS: [Field; 13] = [
+    ("hdw", |anal| anal.hdw, true),
+    ("blow_up_dt", |anal| anal.blow_up_dt.unpack(), true),
+    ("blow_up_height", |anal| anal.blow_up_height.unpack(), true),
+    ("lead_time_hours", |anal| f64::from(anal.lead_time), false),
+    ("ffwi", |anal| anal.ffwi, true),
+    ("haines_index", |anal| f64::from(anal.haines_index), false),
+    ("mixing_height", |anal| anal.mixing_height.unpack(), true),
+    ("surface_rh", |anal| anal.surface_rh, true),
+    (
+        "surface_temperature_c",
+        |anal| anal.surface_temperature_c,
+        true,
+    ),
+    (
+        "surface_wind_speed_ms",
+        |anal| anal.surface_wind_speed_ms,
+        true,
+    ),
+    (
+        "surface_wind_direction_deg",
+        |anal| anal.surface_wind_direction_deg,
+        true,
+    ),
+    (
+        "transport_wind_speed_ms",
+        |anal| anal.transport_wind_speed_ms,
+        true,
+    ),
+    (
+        "transport_wind_dir_deg",
+        |anal| anal.transport_wind_dir_deg,
+        true,
+    ),
+];
+
+/// The names of the `FIELDS`, for `AnalyzedData::field_names`.
+const FIELD_NAMES: [&str; FIELDS.len()] = {
+    let mut names = [""; FIELDS.len()];
+    let mut i = 0;
+    while i < FIELDS.len() {
+        names[i] = FIELDS[i].0;
+        i += 1;
+    }
+    names
+};
+
 impl ValidTime for AnalyzedData {
     fn valid_time(&self) -> Option<NaiveDateTime> {
         Some(self.valid_time)
@@ -25,10 +151,153 @@ impl ModelTimes for AnalyzedData {
     }
 }
 
+/// Two `AnalyzedData` are equal if all their fields are equal, where NaN values are considered
+/// equal to each other so missing data compares as equal.
+impl PartialEq for AnalyzedData {
+    fn eq(&self, other: &Self) -> bool {
+        let eq = |a: f64, b: f64| (a.is_nan() && b.is_nan()) || a == b;
+
+        self.valid_time == other.valid_time
+            && self.lead_time == other.lead_time
+            && eq(self.hdw, other.hdw)
+            && eq(self.ffwi, other.ffwi)
+            && eq(self.blow_up_dt.unpack(), other.blow_up_dt.unpack())
+            && eq(self.blow_up_height.unpack(), other.blow_up_height.unpack())
+            && self.haines_index == other.haines_index
+            && eq(self.mixing_height.unpack(), other.mixing_height.unpack())
+            && eq(self.surface_rh, other.surface_rh)
+            && eq(self.surface_temperature_c, other.surface_temperature_c)
+            && eq(self.surface_wind_speed_ms, other.surface_wind_speed_ms)
+            && eq(
+                self.surface_wind_direction_deg,
+                other.surface_wind_direction_deg,
+            )
+            && eq(self.transport_wind_speed_ms, other.transport_wind_speed_ms)
+            && eq(self.transport_wind_dir_deg, other.transport_wind_dir_deg)
+    }
+}
+
+impl Eq for AnalyzedData {}
+
+impl Display for AnalyzedData {
+    /// A compact summary of the values, missing values are shown as NaN.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} +{}h hdw:{:.0} ffwi:{:.1} blow_up_dt:{:.1}C blow_up_height:{:.0}m haines:{} \
+             mixing_height:{:.0}m rh:{:.0}% t:{:.1}C wind:{:.0}@{:.1}m/s \
+             transport:{:.0}@{:.1}m/s",
+            self.valid_time.format(GP_DATE_FORMAT),
+            self.lead_time,
+            self.hdw,
+            self.ffwi,
+            self.blow_up_dt.unpack(),
+            self.blow_up_height.unpack(),
+            self.haines_index,
+            self.mixing_height.unpack(),
+            self.surface_rh,
+            self.surface_temperature_c,
+            self.surface_wind_direction_deg,
+            self.surface_wind_speed_ms,
+            self.transport_wind_dir_deg,
+            self.transport_wind_speed_ms
+        )
+    }
+}
+
+impl Interpolatable for AnalyzedData {
+    fn interpolate(a: &Self, b: &Self, frac: f64) -> Self {
+        let lerp = |a: f64, b: f64| a + (b - a) * frac;
+        // Interpolate directions the short way around, e.g. halfway from 350 to 10 is 0.
+        let lerp_dir = |a: f64, b: f64| {
+            let diff = (b - a + 540.0) % 360.0 - 180.0;
+            (a + diff * frac).rem_euclid(360.0)
+        };
+
+        let dt_secs = (b.valid_time - a.valid_time).num_seconds() as f64;
+        let valid_time = a.valid_time + Duration::seconds((dt_secs * frac).round() as i64);
+        let lead_time = lerp(f64::from(a.lead_time), f64::from(b.lead_time)).round() as i32;
+
+        AnalyzedData {
+            valid_time,
+            lead_time,
+            hdw: lerp(a.hdw, b.hdw),
+            ffwi: lerp(a.ffwi, b.ffwi),
+            blow_up_dt: CelsiusDiff(lerp(a.blow_up_dt.unpack(), b.blow_up_dt.unpack())),
+            blow_up_height: Meters(lerp(a.blow_up_height.unpack(), b.blow_up_height.unpack())),
+            // The Haines Index is categorical, so use the nearest value.
+            haines_index: if frac < 0.5 {
+                a.haines_index
+            } else {
+                b.haines_index
+            },
+            mixing_height: Meters(lerp(a.mixing_height.unpack(), b.mixing_height.unpack())),
+            surface_rh: lerp(a.surface_rh, b.surface_rh),
+            surface_temperature_c: lerp(a.surface_temperature_c, b.surface_temperature_c),
+            surface_wind_speed_ms: lerp(a.surface_wind_speed_ms, b.surface_wind_speed_ms),
+            surface_wind_direction_deg: lerp_dir(
+                a.surface_wind_direction_deg,
+                b.surface_wind_direction_deg,
+            ),
+            transport_wind_speed_ms: lerp(a.transport_wind_speed_ms, b.transport_wind_speed_ms),
+            transport_wind_dir_deg: lerp_dir(a.transport_wind_dir_deg, b.transport_wind_dir_deg),
+        }
+    }
+
+    fn missing(nearest: &Self, valid_time: NaiveDateTime) -> Self {
+        let lead_time = nearest.lead_time + (valid_time - nearest.valid_time).num_hours() as i32;
+
+        AnalyzedData {
+            valid_time,
+            lead_time,
+            hdw: std::f64::NAN,
+            ffwi: std::f64::NAN,
+            blow_up_dt: CelsiusDiff(std::f64::NAN),
+            blow_up_height: Meters(std::f64::NAN),
+            haines_index: 0,
+            mixing_height: Meters(std::f64::NAN),
+            surface_rh: std::f64::NAN,
+            surface_temperature_c: std::f64::NAN,
+            surface_wind_speed_ms: std::f64::NAN,
+            surface_wind_direction_deg: std::f64::NAN,
+            transport_wind_speed_ms: std::f64::NAN,
+            transport_wind_dir_deg: std::f64::NAN,
+        }
+    }
+}
+
+#[cfg(test)]
+impl AnalyzedData {
+    /// An analysis with only the HDW set, every other value is missing. For building test data.
+    pub(crate) fn with_hdw(valid_time: NaiveDateTime, hdw: f64) -> Self {
+        AnalyzedData {
+            valid_time,
+            lead_time: 0,
+            hdw,
+            ffwi: std::f64::NAN,
+            blow_up_dt: CelsiusDiff(std::f64::NAN),
+            blow_up_height: Meters(std::f64::NAN),
+            haines_index: 0,
+            mixing_height: Meters(std::f64::NAN),
+            surface_rh: std::f64::NAN,
+            surface_temperature_c: std::f64::NAN,
+            surface_wind_speed_ms: std::f64::NAN,
+            surface_wind_direction_deg: std::f64::NAN,
+            transport_wind_speed_ms: std::f64::NAN,
+            transport_wind_dir_deg: std::f64::NAN,
+        }
+    }
+}
+
 impl AnalyzedData {
     /// Convert a `sounding_analysis::Analysis` into an `AnalyzedData` struct.
     pub fn analyze(snd: &Sounding) -> Option<Self> {
-        const MIN_BLOWUP: Meters = Meters(2000.0);
+        Self::analyze_with_config(snd, &AnalysisConfig::default())
+    }
+
+    /// Convert a `sounding_analysis::Analysis` into an `AnalyzedData` struct using the provided
+    /// configuration.
+    pub fn analyze_with_config(snd: &Sounding, cfg: &AnalysisConfig) -> Option<Self> {
         const DEFAULT_BLOWUP: (CelsiusDiff, Meters) =
             (CelsiusDiff(std::f64::NAN), Meters(std::f64::NAN));
 
@@ -36,12 +305,24 @@ impl AnalyzedData {
         let lead_time = snd.lead_time().into_option()?;
 
         let hdw = hot_dry_windy(snd).unwrap_or(std::f64::NAN);
+        let ffwi = fosberg_fire_weather_index(snd).unwrap_or(std::f64::NAN);
+        let haines_index = haines(snd).unwrap_or(0);
+        let mixing_height = mixing_height(snd).unwrap_or(Meters(std::f64::NAN));
+        let surface_rh = surface_rh(snd).unwrap_or(std::f64::NAN);
+        let surface_temperature_c = surface_temperature(snd)
+            .map(|t| t.unpack())
+            .unwrap_or(std::f64::NAN);
+        let (surface_wind_speed_ms, surface_wind_direction_deg) = surface_wind(snd)
+            .map(|wind| (MetersPSec::from(wind.speed).unpack(), wind.direction))
+            .unwrap_or((std::f64::NAN, std::f64::NAN));
+        let (transport_wind_speed_ms, transport_wind_dir_deg) =
+            transport_wind(snd, mixing_height).unwrap_or((std::f64::NAN, std::f64::NAN));
         let (delta_t, height) = blow_up(snd, None)
             // Extract the values I need to plot
             .map(|bua| (bua.delta_t_lmib, bua.delta_z_lmib))
             // Plot a blank space (so use NAN marker) where there isn't a minimal blow up
             .map(|(dt, hgt)| {
-                if hgt > MIN_BLOWUP {
+                if hgt > cfg.min_blowup {
                     (dt, hgt)
                 } else {
                     DEFAULT_BLOWUP
@@ -53,8 +334,272 @@ impl AnalyzedData {
             valid_time,
             lead_time,
             hdw,
+            ffwi,
             blow_up_dt: delta_t,
             blow_up_height: height,
+            haines_index,
+            mixing_height,
+            surface_rh,
+            surface_temperature_c,
+            surface_wind_speed_ms,
+            surface_wind_direction_deg,
+            transport_wind_speed_ms,
+            transport_wind_dir_deg,
         })
     }
+
+    /// Check if any of the floating point fields are NaN.
+    ///
+    /// The blow up fields are NaN when there is no significant blow up, so they are often NaN.
+    pub fn is_any_nan(&self) -> bool {
+        self.nan_fields().next().is_some()
+    }
+
+    /// The names of the fields that are NaN, in the order given by `field_names`.
+    pub fn nan_field_names(&self) -> Vec<&'static str> {
+        self.nan_fields().collect()
+    }
+
+    /// The number of fields that are NaN.
+    pub fn nan_count(&self) -> usize {
+        self.nan_fields().count()
+    }
+
+    /// All the numeric fields as `f64`, in the order given by `field_names`.
+    ///
+    /// The order is part of the public API, new fields are only ever added at the end. The lead
+    /// time is in hours and a missing Haines Index is 0.
+    pub fn to_f64_vec(&self) -> Vec<f64> {
+        FIELDS.iter().map(|(_, value, _)| value(self)).collect()
+    }
+
+    /// The names of the values returned by `to_f64_vec`, in the same order.
+    pub fn field_names() -> &'static [&'static str] {
+        &FIELD_NAMES
+    }
+
+    /// The names of the floating point fields that are NaN.
+    fn nan_fields(&self) -> impl Iterator<Item = &'static str> + '_ {
+        FIELDS
+            .iter()
+            .filter(move |(_, value, is_float)| *is_float && value(self).is_nan())
+            .map(|(name, _, _)| *name)
+    }
+}
+
+/// Calculate the Fosberg Fire Weather Index from the surface temperature, dew point, and wind.
+fn fosberg_fire_weather_index(snd: &Sounding) -> Option<f64> {
+    let temperature = snd.sfc_temperature().into_option()?;
+    let dew_point = snd.sfc_dew_point().into_option()?;
+    let wind = snd.sfc_wind().into_option()?;
+
+    // The Fosberg formula is defined in terms of degrees Fahrenheit, percent, and miles per hour.
+    let t_f = temperature.unpack() * 9.0 / 5.0 + 32.0;
+    let rh_pct = rh(temperature, dew_point)? * 100.0;
+    let spd_mph = wind.speed.unpack() * 1.150_78;
+
+    // Equilibrium moisture content
+    let emc = if rh_pct < 10.0 {
+        0.032_29 + 0.281_073 * rh_pct - 0.000_578 * rh_pct * t_f
+    } else if rh_pct <= 50.0 {
+        2.227_49 + 0.160_107 * rh_pct - 0.014_784 * t_f
+    } else {
+        21.0606 + 0.005_565 * rh_pct * rh_pct - 0.000_35 * rh_pct * t_f - 0.483_199 * rh_pct
+    };
+
+    // Moisture damping coefficient
+    let m = emc / 30.0;
+    let eta = 1.0 - 2.0 * m + 1.5 * m * m - 0.5 * m * m * m;
+
+    Some(eta * (1.0 + spd_mph * spd_mph).sqrt() / 0.3002)
+}
+
+/// Get the surface temperature, or the temperature at the lowest level with one if the surface
+/// value is missing.
+fn surface_temperature(snd: &Sounding) -> Option<Celsius> {
+    snd.sfc_temperature().into_option().or_else(|| {
+        snd.temperature_profile()
+            .iter()
+            .find_map(|t| t.into_option())
+    })
+}
+
+/// Get the surface wind, or the wind at the lowest level with one if the surface value is
+/// missing.
+fn surface_wind(snd: &Sounding) -> Option<WindSpdDir<Knots>> {
+    snd.sfc_wind()
+        .into_option()
+        .or_else(|| snd.wind_profile().iter().find_map(|w| w.into_option()))
+}
+
+/// Calculate the relative humidity in percent at the surface, or at the lowest level with a
+/// temperature and dew point if the surface values are missing.
+fn surface_rh(snd: &Sounding) -> Option<f64> {
+    let sfc = snd
+        .sfc_temperature()
+        .into_option()
+        .and_then(|t| Some((t, snd.sfc_dew_point().into_option()?)));
+
+    let (temperature, dew_point) = sfc.or_else(|| {
+        snd.temperature_profile()
+            .iter()
+            .zip(snd.dew_point_profile())
+            .find_map(|(t, td)| Some((t.into_option()?, td.into_option()?)))
+    })?;
+
+    rh(temperature, dew_point).map(|rh| rh * 100.0)
+}
+
+/// Calculate the mixing height above ground level with the parcel method, which is the height
+/// where the potential temperature first exceeds the potential temperature at the surface.
+///
+/// `sounding_analysis` doesn't have a mixing height routine, so the parcel method is done here. It
+/// lifts a dry adiabatic parcel from the lowest level, the usual definition for fire weather.
+fn mixing_height(snd: &Sounding) -> Option<Meters> {
+    let levels = izip!(
+        snd.pressure_profile(),
+        snd.temperature_profile(),
+        snd.height_profile()
+    )
+    .filter_map(|(p, t, h)| Some((p.into_option()?, t.into_option()?, h.into_option()?)))
+    .map(|(p, t, h)| (potential_temperature(p, t).unpack(), h.unpack()));
+
+    parcel_mixing_height(levels)
+}
+
+/// Find the mixing height above the first level from (potential temperature, height) levels
+/// ordered from the bottom up, interpolating linearly between the levels.
+///
+/// Returns `None` if there are no levels or the potential temperature never exceeds the value at
+/// the first level.
+fn parcel_mixing_height<I: Iterator<Item = (f64, f64)>>(mut levels: I) -> Option<Meters> {
+    let (sfc_theta, sfc_height) = levels.next()?;
+
+    let mut prev = (sfc_theta, sfc_height);
+    for (theta, height) in levels {
+        if theta > sfc_theta {
+            let (prev_theta, prev_height) = prev;
+            let frac = (sfc_theta - prev_theta) / (theta - prev_theta);
+            let mix_height = prev_height + frac * (height - prev_height);
+
+            return Some(Meters(mix_height - sfc_height));
+        }
+        prev = (theta, height);
+    }
+
+    None
+}
+
+/// The minimum number of levels with wind in the mixing layer to calculate the transport wind.
+const MIN_TRANSPORT_WIND_LEVELS: usize = 2;
+
+/// Calculate the transport wind, the mean wind through the mixing layer, as the speed in meters
+/// per second and the direction in degrees.
+///
+/// The winds are averaged as u and v components from the lowest level with a height up to
+/// `mixing_height` above it, the same layer as `mixing_height`. If the mixing height is missing or
+/// there are too few levels with wind in the layer, the result is `None`.
+fn transport_wind(snd: &Sounding, mixing_height: Meters) -> Option<(f64, f64)> {
+    let mixing_height = mixing_height.into_option()?;
+    let sfc_height = snd
+        .height_profile()
+        .iter()
+        .find_map(|h| h.into_option())?
+        .unpack();
+    let top = sfc_height + mixing_height;
+
+    let (u_sum, v_sum, count) = snd
+        .height_profile()
+        .iter()
+        .zip(snd.wind_profile())
+        .filter_map(|(h, w)| Some((h.into_option()?.unpack(), w.into_option()?)))
+        .take_while(|&(h, _)| h <= top)
+        .map(|(_, wind)| {
+            let spd = MetersPSec::from(wind.speed).unpack();
+            let dir = wind.direction.to_radians();
+            // Meteorological convention, the direction the wind is blowing from.
+            (-spd * dir.sin(), -spd * dir.cos())
+        })
+        .fold((0.0, 0.0, 0), |(u_sum, v_sum, count), (u, v)| {
+            (u_sum + u, v_sum + v, count + 1)
+        });
+
+    if count < MIN_TRANSPORT_WIND_LEVELS {
+        return None;
+    }
+
+    let u = u_sum / count as f64;
+    let v = v_sum / count as f64;
+    let speed = u.hypot(v);
+    let direction = (-u).atan2(-v).to_degrees().rem_euclid(360.0);
+
+    Some((speed, direction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn anal() -> AnalyzedData {
+        let valid_time = NaiveDate::from_ymd_opt(2020, 8, 1)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .unwrap();
+
+        AnalyzedData::with_hdw(valid_time, 100.0)
+    }
+
+    #[test]
+    fn field_names_match_to_f64_vec() {
+        let anal = anal();
+        let vals = anal.to_f64_vec();
+
+        assert_eq!(vals.len(), AnalyzedData::field_names().len());
+        assert_eq!(
+            &AnalyzedData::field_names()[..4],
+            &["hdw", "blow_up_dt", "blow_up_height", "lead_time_hours"]
+        );
+        assert_eq!(vals[0], 100.0);
+    }
+
+    #[test]
+    fn nan_fields_skip_integer_fields() {
+        let anal = anal();
+
+        assert!(anal.is_any_nan());
+        assert_eq!(anal.nan_count(), 10);
+        assert!(!anal.nan_field_names().contains(&"hdw"));
+        assert!(!anal.nan_field_names().contains(&"lead_time_hours"));
+        assert!(!anal.nan_field_names().contains(&"haines_index"));
+    }
+
+    #[test]
+    fn mixing_height_interpolates_where_the_parcel_stops() {
+        // The surface is at 1000 m, the parcel is warmer than the environment until the
+        // potential temperature passes 300 K a third of the way from 2000 m to 2500 m.
+        let levels = vec![
+            (300.0, 1000.0),
+            (300.0, 1500.0),
+            (299.0, 2000.0),
+            (302.0, 2500.0),
+        ];
+
+        let height = parcel_mixing_height(levels.into_iter()).unwrap().unpack();
+        assert!((height - (1000.0 + 500.0 / 3.0)).abs() < 1.0e-9);
+    }
+
+    #[test]
+    fn mixing_height_is_zero_when_stable_at_the_surface() {
+        let levels = vec![(300.0, 1000.0), (301.0, 1100.0), (305.0, 1500.0)];
+
+        assert_eq!(parcel_mixing_height(levels.into_iter()), Some(Meters(0.0)));
+    }
+
+    #[test]
+    fn mixing_height_is_none_without_a_cap() {
+        let levels = vec![(300.0, 1000.0), (299.0, 1500.0), (298.0, 2000.0)];
+
+        assert_eq!(parcel_mixing_height(levels.into_iter()), None);
+        assert_eq!(parcel_mixing_height(std::iter::empty()), None);
+    }
 }
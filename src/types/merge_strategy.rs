@@ -0,0 +1,51 @@
+use crate::{
+    timeseries::{EnsembleSeries, MergedSeries, ModelTimes},
+    types::AnalyzedData,
+};
+use std::cmp::Ordering;
+
+/// Common strategies for choosing which ensemble member to keep at each valid time when merging
+/// an `EnsembleSeries<AnalyzedData>`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep the member with the shortest lead time, the same as `EnsembleSeries::merge`.
+    #[default]
+    ShortestLeadTime,
+    /// Keep the member with the largest HDW, useful for worst case planning.
+    MaxHdw,
+    /// Keep the member with the smallest HDW.
+    MinHdw,
+}
+
+impl MergeStrategy {
+    /// Order two values so the one this strategy prefers comes first.
+    ///
+    /// For the HDW strategies, NaN values are always ordered last.
+    pub fn compare(self, a: &AnalyzedData, b: &AnalyzedData) -> Ordering {
+        match self {
+            MergeStrategy::ShortestLeadTime => a.lead_time().cmp(&b.lead_time()),
+            MergeStrategy::MaxHdw => compare_nan_last(a.hdw, b.hdw, |x, y| y.partial_cmp(&x)),
+            MergeStrategy::MinHdw => compare_nan_last(a.hdw, b.hdw, |x, y| x.partial_cmp(&y)),
+        }
+    }
+}
+
+/// Compare `a` and `b` with `cmp`, but always order NaN values last.
+fn compare_nan_last<F>(a: f64, b: f64, cmp: F) -> Ordering
+where
+    F: Fn(f64, f64) -> Option<Ordering>,
+{
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => cmp(a, b).unwrap_or(Ordering::Equal),
+    }
+}
+
+impl EnsembleSeries<AnalyzedData> {
+    /// Transform into a `MergedSeries` using one of the common merge strategies.
+    pub fn merge_by(self, strategy: MergeStrategy) -> MergedSeries<AnalyzedData> {
+        self.merge_with_strategy(|a, b| strategy.compare(a, b))
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{timeseries::EnsembleSeries, types::AnalyzedData};
+use chrono::NaiveDateTime;
+use metfor::Quantity;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A flattened `AnalyzedData` with the meta data for the run it came from, for exporting to other
+/// tools like dataframe libraries.
+///
+/// All the fields are plain values, quantities are in the units given in the field names. Missing
+/// values are NaN, and a missing Haines Index is 0.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AnalyzedRecord {
+    /// The site name, or the station number if the site has no name.
+    pub site_id: String,
+    pub station_num: u32,
+    pub model: String,
+    pub init_time: NaiveDateTime,
+    pub valid_time: NaiveDateTime,
+    pub lead_time: i32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub hdw: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub ffwi: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub blow_up_dt_c: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub blow_up_height_m: f64,
+    pub haines_index: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub mixing_height_m: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub surface_rh: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub surface_temperature_c: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub surface_wind_speed_ms: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub surface_wind_direction_deg: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub transport_wind_speed_ms: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::nan_as_null"))]
+    pub transport_wind_dir_deg: f64,
+}
+
+impl EnsembleSeries<AnalyzedData> {
+    /// Flatten all the ensemble members into a list of records, one for each time step in each
+    /// run.
+    ///
+    /// The records are ordered by initialization time and then valid time, the same as the
+    /// ensemble.
+    pub fn to_records(&self) -> Vec<AnalyzedRecord> {
+        let site = &self.meta.site;
+        let station_num = u32::from(site.station_num);
+        let site_id = site.name.clone().unwrap_or_else(|| station_num.to_string());
+
+        self.data
+            .iter()
+            .flat_map(|(init_time, time_series)| {
+                time_series.iter().map(move |anal| (*init_time, anal))
+            })
+            .map(|(init_time, anal)| AnalyzedRecord {
+                site_id: site_id.clone(),
+                station_num,
+                model: self.meta.model.clone(),
+                init_time,
+                valid_time: anal.valid_time,
+                lead_time: anal.lead_time,
+                hdw: anal.hdw,
+                ffwi: anal.ffwi,
+                blow_up_dt_c: anal.blow_up_dt.unpack(),
+                blow_up_height_m: anal.blow_up_height.unpack(),
+                haines_index: anal.haines_index,
+                mixing_height_m: anal.mixing_height.unpack(),
+                surface_rh: anal.surface_rh,
+                surface_temperature_c: anal.surface_temperature_c,
+                surface_wind_speed_ms: anal.surface_wind_speed_ms,
+                surface_wind_direction_deg: anal.surface_wind_direction_deg,
+                transport_wind_speed_ms: anal.transport_wind_speed_ms,
+                transport_wind_dir_deg: anal.transport_wind_dir_deg,
+            })
+            .collect()
+    }
+}
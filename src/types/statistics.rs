@@ -0,0 +1,347 @@
+use crate::{
+    climo::ClimoSource,
+    stats::quantile,
+    timeseries::{EnsembleSeries, MergedSeries, ValidTime},
+    types::AnalyzedData,
+};
+use bufcli::{ClimoElement, Percentile};
+use chrono::{Duration, NaiveDateTime};
+use metfor::Quantity;
+use std::collections::{BTreeMap, HashMap};
+
+/// Summary statistics describing the spread of the ensemble members for a single field at a
+/// single valid time.
+///
+/// If there are fewer than two non-NaN values, all the statistics are NaN.
+#[derive(Clone, Copy, Debug)]
+pub struct SpreadStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+/// The ensemble spread statistics for each analyzed field at a single valid time.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeStepStats {
+    pub valid_time: NaiveDateTime,
+    pub hdw: SpreadStats,
+    pub blow_up_dt: SpreadStats,
+    pub blow_up_height: SpreadStats,
+}
+
+impl SpreadStats {
+    /// Calculate the statistics, skipping any NaN values.
+    fn from_values<I: Iterator<Item = f64>>(vals: I) -> Self {
+        let vals: Vec<f64> = vals.filter(|val| !val.is_nan()).collect();
+
+        if vals.len() < 2 {
+            return SpreadStats {
+                min: std::f64::NAN,
+                max: std::f64::NAN,
+                mean: std::f64::NAN,
+                std_dev: std::f64::NAN,
+            };
+        }
+
+        let n = vals.len() as f64;
+        let min = vals.iter().cloned().fold(std::f64::INFINITY, f64::min);
+        let max = vals.iter().cloned().fold(std::f64::NEG_INFINITY, f64::max);
+        let mean = vals.iter().sum::<f64>() / n;
+        let variance = vals.iter().map(|val| (val - mean).powi(2)).sum::<f64>() / (n - 1.0);
+
+        SpreadStats {
+            min,
+            max,
+            mean,
+            std_dev: variance.sqrt(),
+        }
+    }
+}
+
+/// Aggregate a field across all the ensemble members at each valid time.
+///
+/// The `*_by_hour` methods are provided in terms of `aggregate_by_hour`, so new convenience
+/// methods only need to select a field and an aggregation.
+pub trait EnsembleFieldStats {
+    /// Collect the non-NaN values selected by `field_fn` from every ensemble member at each valid
+    /// time, and reduce them to a single value with `aggregate`.
+    ///
+    /// If all the values at a valid time are NaN, the result is NaN. The results are sorted by
+    /// valid time.
+    fn aggregate_by_hour<F, A>(&self, field_fn: F, aggregate: A) -> Vec<(NaiveDateTime, f64)>
+    where
+        F: Fn(&AnalyzedData) -> f64,
+        A: Fn(&[f64]) -> f64;
+
+    /// The maximum HDW across the ensemble members at each valid time.
+    fn max_hdw_by_hour(&self) -> Vec<(NaiveDateTime, f64)> {
+        self.aggregate_by_hour(|anal| anal.hdw, max)
+    }
+
+    /// The minimum HDW across the ensemble members at each valid time.
+    fn min_hdw_by_hour(&self) -> Vec<(NaiveDateTime, f64)> {
+        self.aggregate_by_hour(|anal| anal.hdw, min)
+    }
+
+    /// The mean HDW across the ensemble members at each valid time.
+    fn mean_hdw_by_hour(&self) -> Vec<(NaiveDateTime, f64)> {
+        self.aggregate_by_hour(|anal| anal.hdw, mean)
+    }
+}
+
+impl EnsembleFieldStats for EnsembleSeries<AnalyzedData> {
+    fn aggregate_by_hour<F, A>(&self, field_fn: F, aggregate: A) -> Vec<(NaiveDateTime, f64)>
+    where
+        F: Fn(&AnalyzedData) -> f64,
+        A: Fn(&[f64]) -> f64,
+    {
+        self.group_by_valid_time()
+            .into_iter()
+            .map(|(valid_time, anals)| {
+                let vals: Vec<f64> = anals
+                    .into_iter()
+                    .map(&field_fn)
+                    .filter(|val| !val.is_nan())
+                    .collect();
+
+                let agg = if vals.is_empty() {
+                    std::f64::NAN
+                } else {
+                    aggregate(&vals)
+                };
+
+                (valid_time, agg)
+            })
+            .collect()
+    }
+}
+
+fn max(vals: &[f64]) -> f64 {
+    vals.iter().cloned().fold(std::f64::NEG_INFINITY, f64::max)
+}
+
+fn min(vals: &[f64]) -> f64 {
+    vals.iter().cloned().fold(std::f64::INFINITY, f64::min)
+}
+
+fn mean(vals: &[f64]) -> f64 {
+    vals.iter().sum::<f64>() / vals.len() as f64
+}
+
+impl EnsembleSeries<AnalyzedData> {
+    /// Calculate the spread of the ensemble members at each valid time.
+    ///
+    /// The results are sorted by valid time.
+    pub fn statistics(&self) -> Vec<TimeStepStats> {
+        self.group_by_valid_time()
+            .into_iter()
+            .map(|(valid_time, anals)| TimeStepStats {
+                valid_time,
+                hdw: SpreadStats::from_values(anals.iter().map(|anal| anal.hdw)),
+                blow_up_dt: SpreadStats::from_values(
+                    anals.iter().map(|anal| anal.blow_up_dt.unpack()),
+                ),
+                blow_up_height: SpreadStats::from_values(
+                    anals.iter().map(|anal| anal.blow_up_height.unpack()),
+                ),
+            })
+            .collect()
+    }
+
+    /// Calculate the fraction of ensemble members with a value exceeding `threshold` at each
+    /// valid time.
+    ///
+    /// The `field_fn` selects the value to compare from each `AnalyzedData`. NaN values are
+    /// skipped, and if all the values at a valid time are NaN the probability is NaN. The results
+    /// are sorted by valid time.
+    pub fn probability_exceeding<F>(&self, field_fn: F, threshold: f64) -> Vec<(NaiveDateTime, f64)>
+    where
+        F: Fn(&AnalyzedData) -> f64,
+    {
+        self.group_by_valid_time()
+            .into_iter()
+            .map(|(valid_time, anals)| {
+                let vals: Vec<f64> = anals
+                    .into_iter()
+                    .map(&field_fn)
+                    .filter(|val| !val.is_nan())
+                    .collect();
+
+                let prob = if vals.is_empty() {
+                    std::f64::NAN
+                } else {
+                    let count = vals.iter().filter(|&&val| val > threshold).count();
+                    count as f64 / vals.len() as f64
+                };
+
+                (valid_time, prob)
+            })
+            .collect()
+    }
+
+    /// Calculate the `q`-th quantile, from 0.0 to 1.0, of the ensemble members' HDW at each valid
+    /// time.
+    ///
+    /// NaN values are skipped, and if all the values at a valid time are NaN the quantile is NaN.
+    /// The results are sorted by valid time.
+    pub fn hdw_quantile_series(&self, q: f64) -> Vec<(NaiveDateTime, f64)> {
+        self.group_by_valid_time()
+            .into_iter()
+            .map(|(valid_time, anals)| {
+                let vals: Vec<f64> = anals.iter().map(|anal| anal.hdw).collect();
+                (valid_time, quantile(&vals, q))
+            })
+            .collect()
+    }
+
+    /// Collect the HDW from all of the ensemble members at each valid time.
+    ///
+    /// NaN values are skipped, so the list for a valid time is empty if all of its values are NaN.
+    /// The results are sorted by valid time.
+    pub fn to_grouped_by_valid_time(&self) -> Vec<(NaiveDateTime, Vec<f64>)> {
+        self.to_grouped_field_by_valid_time(|anal| anal.hdw)
+    }
+
+    /// Collect a value from all of the ensemble members at each valid time.
+    ///
+    /// The `field_fn` selects the value from each `AnalyzedData`. NaN values are skipped, so the
+    /// list for a valid time is empty if all of its values are NaN. The results are sorted by valid
+    /// time.
+    pub fn to_grouped_field_by_valid_time<F>(&self, field_fn: F) -> Vec<(NaiveDateTime, Vec<f64>)>
+    where
+        F: Fn(&AnalyzedData) -> f64,
+    {
+        self.group_by_valid_time()
+            .into_iter()
+            .map(|(valid_time, anals)| {
+                let vals: Vec<f64> = anals
+                    .into_iter()
+                    .map(&field_fn)
+                    .filter(|val| !val.is_nan())
+                    .collect();
+
+                (valid_time, vals)
+            })
+            .collect()
+    }
+
+    /// Collect all of the ensemble members' values by valid time.
+    fn group_by_valid_time(&self) -> BTreeMap<NaiveDateTime, Vec<&AnalyzedData>> {
+        let mut pool: BTreeMap<NaiveDateTime, Vec<&AnalyzedData>> = BTreeMap::new();
+
+        for (_init_time, time_series) in self.data.iter() {
+            for anal in time_series.as_ref().iter() {
+                if let Some(valid_time) = anal.valid_time() {
+                    pool.entry(valid_time).or_default().push(anal);
+                }
+            }
+        }
+
+        pool
+    }
+}
+
+impl MergedSeries<AnalyzedData> {
+    /// Calculate the normalized anomaly of the HDW relative to climatology at each valid time.
+    ///
+    /// The anomaly is `(value - median) / (90th percentile - 10th percentile)`. If the climatology
+    /// is not available for a valid time, or the denominator is zero, the anomaly is NaN.
+    pub fn anomaly<C: ClimoSource>(&self, climo: &mut C) -> Vec<(NaiveDateTime, f64)> {
+        let MergedSeries { meta, data } = self;
+
+        let hourly_deciles: HashMap<NaiveDateTime, _> = climo
+            .hourly_deciles(
+                &meta.site,
+                &meta.model,
+                ClimoElement::HDW,
+                meta.start,
+                meta.end,
+            )
+            .map(|hourly_deciles| hourly_deciles.into_iter().collect())
+            .unwrap_or_default();
+
+        data.as_ref()
+            .iter()
+            .map(|anal| {
+                let anomaly = hourly_deciles
+                    .get(&anal.valid_time)
+                    .map(|deciles| {
+                        let median = deciles.value_at_percentile(Percentile::from(50));
+                        let denom = deciles.value_at_percentile(Percentile::from(90))
+                            - deciles.value_at_percentile(Percentile::from(10));
+
+                        if denom == 0.0 {
+                            std::f64::NAN
+                        } else {
+                            (anal.hdw - median) / denom
+                        }
+                    })
+                    .unwrap_or(std::f64::NAN);
+
+                (anal.valid_time, anomaly)
+            })
+            .collect()
+    }
+
+    /// Find the rolling window of length `window` with the highest mean HDW.
+    ///
+    /// A window starts at each time step and includes the time steps before `window` later, only
+    /// windows that end within the series are considered. NaN values are skipped, and if all the
+    /// values are NaN or the series is shorter than `window` the result is `None`. Otherwise the
+    /// result is the time at the center of the window and the mean value.
+    pub fn peak_hdw_window(&self, window: Duration) -> Option<(NaiveDateTime, f64)> {
+        self.peak_window(window, |anal| anal.hdw)
+    }
+
+    /// Find the rolling window of length `window` with the highest mean blow up height in meters.
+    ///
+    /// A window starts at each time step and includes the time steps before `window` later, only
+    /// windows that end within the series are considered. NaN values are skipped, and if all the
+    /// values are NaN or the series is shorter than `window` the result is `None`. Otherwise the
+    /// result is the time at the center of the window and the mean value.
+    pub fn peak_blow_up_height_window(&self, window: Duration) -> Option<(NaiveDateTime, f64)> {
+        self.peak_window(window, |anal| anal.blow_up_height.unpack())
+    }
+
+    /// Find the rolling window of length `window` with the highest mean value selected by
+    /// `field_fn`.
+    ///
+    /// Windows with only NaN values are ignored. If more than one window has the highest mean, the
+    /// earliest is used.
+    fn peak_window<F>(&self, window: Duration, field_fn: F) -> Option<(NaiveDateTime, f64)>
+    where
+        F: Fn(&AnalyzedData) -> f64,
+    {
+        let anals: &[AnalyzedData] = self.data.as_ref();
+        let last_time = anals.last()?.valid_time;
+
+        let mut peak: Option<(NaiveDateTime, f64)> = None;
+        for (i, first) in anals.iter().enumerate() {
+            // Partial windows at the end of the series could beat the full windows with only a
+            // few values, so stop at the first window that ends past the series.
+            let window_end = first.valid_time + window;
+            if window_end > last_time {
+                break;
+            }
+
+            let vals: Vec<f64> = anals[i..]
+                .iter()
+                .take_while(|anal| anal.valid_time < window_end)
+                .map(&field_fn)
+                .filter(|val| !val.is_nan())
+                .collect();
+
+            if vals.is_empty() {
+                continue;
+            }
+
+            let window_mean = mean(&vals);
+            if peak.map(|(_, max)| window_mean > max).unwrap_or(true) {
+                peak = Some((first.valid_time + window / 2, window_mean));
+            }
+        }
+
+        peak
+    }
+}
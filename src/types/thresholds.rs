@@ -0,0 +1,65 @@
+use crate::{timeseries::MergedSeries, types::AnalyzedData};
+use chrono::NaiveDateTime;
+use metfor::{Meters, Quantity};
+use std::fmt::Display;
+
+/// A time step where a parameter exceeded a threshold, e.g. a red flag threshold for a site.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThresholdExceedance {
+    pub valid_time: NaiveDateTime,
+    /// The name of the parameter, e.g. "HDW".
+    pub field: &'static str,
+    pub value: f64,
+    pub threshold: f64,
+}
+
+impl Display for ThresholdExceedance {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} of {:.0} exceeds threshold {:.0}",
+            self.valid_time.format("%Y-%m-%dT%HZ"),
+            self.field,
+            self.value,
+            self.threshold
+        )
+    }
+}
+
+/// Find every time step where the HDW or blow up height exceeds its threshold.
+///
+/// NaN values never exceed a threshold. The results are in the same order as the series, and if
+/// both parameters exceed their thresholds at a time step, both are included.
+pub fn check_thresholds(
+    series: &MergedSeries<AnalyzedData>,
+    hdw_threshold: f64,
+    blowup_height_threshold: Meters,
+) -> Vec<ThresholdExceedance> {
+    let blowup_height_threshold = blowup_height_threshold.unpack();
+
+    let mut exceedances = vec![];
+    for anal in series.data.iter() {
+        let checks = [
+            ("HDW", anal.hdw, hdw_threshold),
+            (
+                "Blow Up Height",
+                anal.blow_up_height.unpack(),
+                blowup_height_threshold,
+            ),
+        ];
+
+        for &(field, value, threshold) in checks.iter() {
+            // Comparisons with NaN are always false.
+            if value > threshold {
+                exceedances.push(ThresholdExceedance {
+                    valid_time: anal.valid_time,
+                    field,
+                    value,
+                    threshold,
+                });
+            }
+        }
+    }
+
+    exceedances
+}
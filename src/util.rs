@@ -0,0 +1,12 @@
+//! Small helpers shared between the output modules.
+
+use crate::timeseries::MetaData;
+
+/// The label for the site in plot titles, the site name if it has one, otherwise the
+/// description of the site.
+pub(crate) fn site_label(meta: &MetaData) -> String {
+    meta.site
+        .name
+        .clone()
+        .unwrap_or_else(|| meta.site.description())
+}
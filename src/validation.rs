@@ -0,0 +1,231 @@
+//! Checks for the quality of analyzed data before it is plotted.
+use crate::{
+    timeseries::{EnsembleSeries, TimeSeries},
+    types::AnalyzedData,
+};
+use chrono::Duration;
+use metfor::Quantity;
+
+/// A summary of data quality issues in an `EnsembleSeries<AnalyzedData>`.
+#[derive(Clone, Debug)]
+pub struct ValidationReport {
+    /// The total number of time steps in all the ensemble members.
+    pub total_time_steps: usize,
+    /// The fraction of time steps with a missing HDW, NaN if there are no time steps.
+    pub nan_fraction_hdw: f64,
+    /// The fraction of time steps with a missing blow up height, NaN if there are no time steps.
+    ///
+    /// Blow up heights below the minimum in the `AnalysisConfig` are missing, so a large fraction
+    /// is expected when the atmosphere is stable.
+    pub nan_fraction_blowup: f64,
+    /// The length of the shortest ensemble member from the first to last valid time.
+    pub shortest_run_hours: i64,
+    /// The length of the longest ensemble member from the first to last valid time.
+    pub longest_run_hours: i64,
+    /// Whether the time steps in any ensemble member are unevenly spaced.
+    pub irregular_spacing: bool,
+    /// Human readable descriptions of any problems found.
+    pub warnings: Vec<String>,
+}
+
+/// Warn if more than this fraction of the HDW values are missing.
+const MAX_NAN_FRACTION_HDW: f64 = 0.25;
+/// Warn if the shortest ensemble member is less than this fraction of the length of the longest.
+const MIN_RUN_LENGTH_FRACTION: f64 = 0.5;
+
+/// Check the ensemble for missing data, short ensemble members, and unevenly spaced time steps.
+pub fn validate(ens: &EnsembleSeries<AnalyzedData>) -> ValidationReport {
+    let all_vals = || {
+        ens.data
+            .iter()
+            .flat_map(|(_, time_series)| time_series.iter())
+    };
+
+    let total_time_steps = ens.total_time_steps();
+    let nan_fraction = |count: usize| {
+        if total_time_steps == 0 {
+            std::f64::NAN
+        } else {
+            count as f64 / total_time_steps as f64
+        }
+    };
+    let nan_fraction_hdw = nan_fraction(all_vals().filter(|anal| anal.hdw.is_nan()).count());
+    let nan_fraction_blowup = nan_fraction(
+        all_vals()
+            .filter(|anal| anal.blow_up_height.unpack().is_nan())
+            .count(),
+    );
+
+    let run_lengths: Vec<i64> = ens
+        .data
+        .iter()
+        .map(|(_, time_series)| run_length(time_series).num_hours())
+        .collect();
+    let shortest_run_hours = run_lengths.iter().cloned().min().unwrap_or(0);
+    let longest_run_hours = run_lengths.iter().cloned().max().unwrap_or(0);
+
+    let irregular_spacing = ens
+        .data
+        .iter()
+        .any(|(_, time_series)| is_irregular(time_series));
+
+    let mut warnings = vec![];
+    if total_time_steps == 0 {
+        warnings.push("there is no data".to_owned());
+    }
+    if nan_fraction_hdw > MAX_NAN_FRACTION_HDW {
+        warnings.push(format!(
+            "{:.0}% of the HDW values are missing",
+            nan_fraction_hdw * 100.0
+        ));
+    }
+    if (shortest_run_hours as f64) < longest_run_hours as f64 * MIN_RUN_LENGTH_FRACTION {
+        warnings.push(format!(
+            "the shortest model run is {} hours, but the longest is {} hours",
+            shortest_run_hours, longest_run_hours
+        ));
+    }
+    if irregular_spacing {
+        warnings.push("the time steps are not evenly spaced".to_owned());
+    }
+
+    ValidationReport {
+        total_time_steps,
+        nan_fraction_hdw,
+        nan_fraction_blowup,
+        shortest_run_hours,
+        longest_run_hours,
+        irregular_spacing,
+        warnings,
+    }
+}
+
+/// The time from the first to the last valid time in a model run.
+fn run_length(time_series: &TimeSeries<AnalyzedData>) -> Duration {
+    match (time_series.as_ref().first(), time_series.as_ref().last()) {
+        (Some(first), Some(last)) => last.valid_time - first.valid_time,
+        _ => Duration::zero(),
+    }
+}
+
+/// Check if the time steps in a model run are unevenly spaced.
+fn is_irregular(time_series: &TimeSeries<AnalyzedData>) -> bool {
+    let vals = time_series.as_ref();
+    let mut steps = vals
+        .iter()
+        .zip(vals.iter().skip(1))
+        .map(|(a, b)| b.valid_time - a.valid_time);
+
+    match steps.next() {
+        Some(first_step) => steps.any(|step| step != first_step),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timeseries::MetaData;
+    use bufkit_data::{SiteInfo, StationNumber};
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn time(hour: i64) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2020, 8, 1)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .unwrap()
+            + Duration::hours(hour)
+    }
+
+    /// A model run with an HDW value at each of `hours` after the start of the day.
+    fn run(hours: &[i64], hdw: &[f64]) -> (NaiveDateTime, TimeSeries<AnalyzedData>) {
+        let data = hours
+            .iter()
+            .zip(hdw.iter())
+            .map(|(&hour, &hdw)| AnalyzedData::with_hdw(time(hour), hdw))
+            .collect();
+
+        (time(hours[0]), data)
+    }
+
+    fn ensemble(
+        data: Vec<(NaiveDateTime, TimeSeries<AnalyzedData>)>,
+    ) -> EnsembleSeries<AnalyzedData> {
+        EnsembleSeries {
+            meta: MetaData {
+                site: SiteInfo {
+                    station_num: StationNumber::from(727_730),
+                    ..SiteInfo::default()
+                },
+                model: "GFS".to_owned(),
+                start: time(0),
+                now: time(0),
+                end: time(48),
+            },
+            data,
+        }
+    }
+
+    #[test]
+    fn nan_fraction_hdw_warns_above_the_limit() {
+        let report = validate(&ensemble(vec![run(
+            &[0, 3, 6, 9],
+            &[1.0, std::f64::NAN, 3.0, 4.0],
+        )]));
+        assert_eq!(report.nan_fraction_hdw, MAX_NAN_FRACTION_HDW);
+        assert!(report.warnings.is_empty());
+
+        let report = validate(&ensemble(vec![run(
+            &[0, 3, 6, 9],
+            &[1.0, std::f64::NAN, std::f64::NAN, 4.0],
+        )]));
+        assert_eq!(report.nan_fraction_hdw, 0.5);
+        assert_eq!(report.warnings, vec!["50% of the HDW values are missing"]);
+    }
+
+    #[test]
+    fn short_runs_warn_below_the_length_fraction() {
+        let report = validate(&ensemble(vec![
+            run(&[0, 12, 24], &[1.0; 3]),
+            run(&[0, 12], &[1.0; 2]),
+        ]));
+        assert_eq!(report.shortest_run_hours, 12);
+        assert_eq!(report.longest_run_hours, 24);
+        assert!(report.warnings.is_empty());
+
+        let report = validate(&ensemble(vec![
+            run(&[0, 12, 24], &[1.0; 3]),
+            run(&[0, 11], &[1.0; 2]),
+        ]));
+        assert_eq!(report.shortest_run_hours, 11);
+        assert_eq!(
+            report.warnings,
+            vec!["the shortest model run is 11 hours, but the longest is 24 hours"]
+        );
+    }
+
+    #[test]
+    fn empty_ensemble() {
+        let report = validate(&ensemble(vec![]));
+
+        assert_eq!(report.total_time_steps, 0);
+        assert!(report.nan_fraction_hdw.is_nan());
+        assert!(report.nan_fraction_blowup.is_nan());
+        assert_eq!(report.shortest_run_hours, 0);
+        assert_eq!(report.longest_run_hours, 0);
+        assert!(!report.irregular_spacing);
+        assert_eq!(report.warnings, vec!["there is no data"]);
+    }
+
+    #[test]
+    fn irregular_spacing() {
+        let report = validate(&ensemble(vec![run(&[0, 3, 6, 9], &[1.0; 4])]));
+        assert!(!report.irregular_spacing);
+
+        let report = validate(&ensemble(vec![run(&[0, 3, 9, 12], &[1.0; 4])]));
+        assert!(report.irregular_spacing);
+        assert_eq!(
+            report.warnings,
+            vec!["the time steps are not evenly spaced"]
+        );
+    }
+}
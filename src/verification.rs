@@ -0,0 +1,215 @@
+//! Compare the forecasts against observations for model evaluation.
+use crate::{
+    timeseries::{MergedSeries, TimeSeries},
+    types::AnalyzedData,
+};
+use chrono::NaiveDateTime;
+use metfor::Quantity;
+use std::collections::HashMap;
+
+/// The verification statistics for a single field.
+///
+/// The statistics are calculated from the forecast minus the observation at each matched valid
+/// time. If there are no matched values they are all NaN, and the correlation is NaN if there are
+/// fewer than two matched values or either set of values is constant.
+#[derive(Clone, Copy, Debug)]
+pub struct FieldVerification {
+    /// The number of valid times with both a forecast and an observed value.
+    pub count: usize,
+    /// The mean error.
+    pub bias: f64,
+    /// The root mean squared error.
+    pub rmse: f64,
+    /// The mean absolute error.
+    pub mae: f64,
+    /// The Pearson correlation coefficient.
+    pub correlation: f64,
+}
+
+/// The verification statistics for each of the continuous fields in `AnalyzedData`.
+///
+/// The Haines Index is categorical and the wind directions wrap around, so they are not verified.
+#[derive(Clone, Copy, Debug)]
+pub struct VerificationStats {
+    pub hdw: FieldVerification,
+    pub ffwi: FieldVerification,
+    pub blow_up_dt: FieldVerification,
+    pub blow_up_height: FieldVerification,
+    pub mixing_height: FieldVerification,
+    pub surface_rh: FieldVerification,
+    pub surface_temperature_c: FieldVerification,
+    pub surface_wind_speed_ms: FieldVerification,
+    pub transport_wind_speed_ms: FieldVerification,
+}
+
+/// Verify the forecast against observations for the same site and period.
+///
+/// The forecast and observations are matched by valid time. For each field, only the valid times
+/// where neither the forecast nor the observation is NaN are used, so gaps in the observations
+/// are skipped.
+pub fn verify(
+    forecast: &MergedSeries<AnalyzedData>,
+    obs: &TimeSeries<AnalyzedData>,
+) -> VerificationStats {
+    let obs: HashMap<NaiveDateTime, &AnalyzedData> =
+        obs.iter().map(|anal| (anal.valid_time, anal)).collect();
+
+    let pairs: Vec<(&AnalyzedData, &AnalyzedData)> = forecast
+        .data
+        .iter()
+        .filter_map(|fcst| Some((fcst, *obs.get(&fcst.valid_time)?)))
+        .collect();
+
+    let field = |field_fn: &dyn Fn(&AnalyzedData) -> f64| {
+        FieldVerification::from_pairs(
+            pairs
+                .iter()
+                .map(|&(fcst, obs)| (field_fn(fcst), field_fn(obs))),
+        )
+    };
+
+    VerificationStats {
+        hdw: field(&|anal| anal.hdw),
+        ffwi: field(&|anal| anal.ffwi),
+        blow_up_dt: field(&|anal| anal.blow_up_dt.unpack()),
+        blow_up_height: field(&|anal| anal.blow_up_height.unpack()),
+        mixing_height: field(&|anal| anal.mixing_height.unpack()),
+        surface_rh: field(&|anal| anal.surface_rh),
+        surface_temperature_c: field(&|anal| anal.surface_temperature_c),
+        surface_wind_speed_ms: field(&|anal| anal.surface_wind_speed_ms),
+        transport_wind_speed_ms: field(&|anal| anal.transport_wind_speed_ms),
+    }
+}
+
+impl FieldVerification {
+    /// Calculate the statistics from (forecast, observed) pairs, skipping pairs with a NaN.
+    fn from_pairs<I: Iterator<Item = (f64, f64)>>(pairs: I) -> Self {
+        let pairs: Vec<(f64, f64)> = pairs
+            .filter(|(fcst, obs)| !fcst.is_nan() && !obs.is_nan())
+            .collect();
+
+        let count = pairs.len();
+        if count == 0 {
+            return FieldVerification {
+                count,
+                bias: std::f64::NAN,
+                rmse: std::f64::NAN,
+                mae: std::f64::NAN,
+                correlation: std::f64::NAN,
+            };
+        }
+
+        let n = count as f64;
+        let mean = |vals: &dyn Fn(&(f64, f64)) -> f64| pairs.iter().map(vals).sum::<f64>() / n;
+
+        let bias = mean(&|(fcst, obs)| fcst - obs);
+        let rmse = mean(&|(fcst, obs)| (fcst - obs) * (fcst - obs)).sqrt();
+        let mae = mean(&|(fcst, obs)| (fcst - obs).abs());
+
+        let fcst_mean = mean(&|(fcst, _)| *fcst);
+        let obs_mean = mean(&|(_, obs)| *obs);
+        let covariance = mean(&|(fcst, obs)| (fcst - fcst_mean) * (obs - obs_mean));
+        let fcst_std_dev = mean(&|(fcst, _)| (fcst - fcst_mean) * (fcst - fcst_mean)).sqrt();
+        let obs_std_dev = mean(&|(_, obs)| (obs - obs_mean) * (obs - obs_mean)).sqrt();
+
+        let correlation = if count < 2 || fcst_std_dev == 0.0 || obs_std_dev == 0.0 {
+            std::f64::NAN
+        } else {
+            covariance / (fcst_std_dev * obs_std_dev)
+        };
+
+        FieldVerification {
+            count,
+            bias,
+            rmse,
+            mae,
+            correlation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timeseries::MetaData;
+    use bufkit_data::SiteInfo;
+    use chrono::{Duration, NaiveDate};
+
+    fn time(hour: i64) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2020, 8, 1)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .unwrap()
+            + Duration::hours(hour)
+    }
+
+    /// A series with an HDW value every hour, starting at 00Z.
+    fn hdw_series(hdw: &[f64]) -> TimeSeries<AnalyzedData> {
+        hdw.iter()
+            .enumerate()
+            .map(|(hour, &hdw)| AnalyzedData::with_hdw(time(hour as i64), hdw))
+            .collect()
+    }
+
+    fn forecast(hdw: &[f64]) -> MergedSeries<AnalyzedData> {
+        MergedSeries {
+            meta: MetaData {
+                site: SiteInfo::default(),
+                model: "GFS".to_owned(),
+                start: time(0),
+                now: time(0),
+                end: time(24),
+            },
+            data: hdw_series(hdw),
+        }
+    }
+
+    #[test]
+    fn hand_computed_statistics() {
+        let stats = verify(
+            &forecast(&[1.0, 2.0, 3.0, 4.0]),
+            &hdw_series(&[2.0, 2.0, 4.0, 6.0]),
+        )
+        .hdw;
+
+        // Errors are -1, 0, -1, -2.
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.bias, -1.0);
+        assert_eq!(stats.rmse, 1.5_f64.sqrt());
+        assert_eq!(stats.mae, 1.0);
+        // Covariance 1.75, variances 1.25 and 2.75.
+        assert!((stats.correlation - 1.75 / (1.25_f64 * 2.75).sqrt()).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn gaps_and_nan_are_skipped() {
+        let fcst = forecast(&[1.0, std::f64::NAN, 3.0, 4.0, 5.0]);
+        // No observation at 04Z, and a missing value at 03Z.
+        let obs = hdw_series(&[2.0, 2.0, 4.0, std::f64::NAN]);
+
+        let stats = verify(&fcst, &obs).hdw;
+
+        // Only 00Z and 02Z match, the errors are -1 and -1.
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.bias, -1.0);
+        assert_eq!(stats.rmse, 1.0);
+        assert_eq!(stats.mae, 1.0);
+        assert_eq!(stats.correlation, 1.0);
+
+        // Nothing matches for the fields that are all NaN.
+        let stats = verify(&fcst, &obs).ffwi;
+        assert_eq!(stats.count, 0);
+        assert!(stats.bias.is_nan());
+        assert!(stats.rmse.is_nan());
+        assert!(stats.mae.is_nan());
+        assert!(stats.correlation.is_nan());
+    }
+
+    #[test]
+    fn constant_series_has_no_correlation() {
+        let stats = verify(&forecast(&[1.0, 2.0, 3.0]), &hdw_series(&[5.0, 5.0, 5.0])).hdw;
+
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.bias, -3.0);
+        assert!(stats.correlation.is_nan());
+    }
+}
@@ -0,0 +1,116 @@
+//! Run the full pipeline from bufkit files to a merged series on synthetic data.
+//!
+//! The files in `tests/fixtures` are two runs of a made up model for KMSO. The run initialized at
+//! 12Z on 2 Sep 2017 has soundings at 12Z, 15Z, and 18Z, and the run initialized at 18Z has
+//! soundings at 18Z and 21Z. Both have a deep, well mixed boundary layer up to about 650 hPa, and
+//! the surface temperature starts at 30C and falls 2C every 3 hours.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use graphs::{
+    drain_receiver, from_messages, load_from_files, parse_sounding, AnalyzedData, FileDataBuilder,
+    MergedSeries,
+};
+use metfor::Quantity;
+use std::path::PathBuf;
+
+fn time(day: u32, hour: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2017, 9, day)
+        .and_then(|date| date.and_hms_opt(hour, 0, 0))
+        .unwrap()
+}
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+fn merged_series() -> MergedSeries<AnalyzedData> {
+    let file_data = FileDataBuilder::new()
+        .site_id("KMSO")
+        .station_num(727730)
+        .model("Synthetic")
+        .start(time(2, 12))
+        .end(time(3, 0))
+        .add_file(fixture("17090212.synthetic_kmso.buf"))
+        .add_file(fixture("17090218.synthetic_kmso.buf"))
+        .build()
+        .unwrap();
+
+    let (mut data, errors) = from_messages(drain_receiver(load_from_files(file_data)).into_iter());
+    assert!(errors.is_empty(), "errors loading files: {:?}", errors);
+    assert_eq!(data.len(), 1);
+
+    let strings = data.pop().unwrap();
+    assert_eq!(strings.len(), 2);
+
+    let (start, end) = (strings.meta.start, strings.meta.end);
+    let soundings = strings.filter_map(|string| parse_sounding(string, start, end));
+    assert_eq!(soundings.len(), 2);
+    assert_eq!(soundings.total_time_steps(), 5);
+
+    soundings.filter_map_inner(AnalyzedData::analyze).merge()
+}
+
+#[test]
+fn merge_picks_the_shortest_lead_time() {
+    let merged = merged_series();
+    assert!(!merged.is_empty());
+
+    let times: Vec<NaiveDateTime> = merged.data.iter().map(|anal| anal.valid_time).collect();
+    assert_eq!(
+        times,
+        vec![time(2, 12), time(2, 15), time(2, 18), time(2, 21)]
+    );
+
+    let lead_times: Vec<i32> = merged.data.iter().map(|anal| anal.lead_time).collect();
+    assert_eq!(lead_times, vec![0, 3, 0, 3]);
+
+    let temperatures: Vec<f64> = merged
+        .data
+        .iter()
+        .map(|anal| anal.surface_temperature_c)
+        .collect();
+    for (temperature, expected) in temperatures.into_iter().zip(&[30.0, 28.0, 30.0, 28.0]) {
+        assert!(
+            (temperature - expected).abs() < 0.5,
+            "surface temperature {} expected {}",
+            temperature,
+            expected
+        );
+    }
+}
+
+#[test]
+fn analyzed_fields_are_valid() {
+    let merged = merged_series();
+
+    for anal in merged.data.iter() {
+        assert!(anal.hdw > 0.0, "hdw {} at {}", anal.hdw, anal.valid_time);
+        assert!(anal.ffwi > 0.0, "ffwi {} at {}", anal.ffwi, anal.valid_time);
+        assert!(
+            (0.0..=100.0).contains(&anal.surface_rh),
+            "surface rh {} at {}",
+            anal.surface_rh,
+            anal.valid_time
+        );
+
+        // The mixed layer extends about 2.8 km above the surface.
+        let mixing_height = anal.mixing_height.unpack();
+        assert!(
+            (2500.0..=3500.0).contains(&mixing_height),
+            "mixing height {} at {}",
+            mixing_height,
+            anal.valid_time
+        );
+
+        // The wind is from the southwest at every level.
+        assert!(
+            (200.0..=270.0).contains(&anal.transport_wind_dir_deg),
+            "transport wind direction {} at {}",
+            anal.transport_wind_dir_deg,
+            anal.valid_time
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! Check that the ensemble types can be shared between threads, e.g. with rayon.
+
+use bufkit_data::SiteInfo;
+use chrono::NaiveDate;
+use graphs::{AnalyzedData, EnsembleSeries, EnsembleSeriesBuilder, TimeSeries};
+use metfor::{CelsiusDiff, Meters};
+use std::sync::{mpsc, Arc};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+fn ensemble() -> EnsembleSeries<AnalyzedData> {
+    let init_time = NaiveDate::from_ymd_opt(2020, 8, 1)
+        .and_then(|date| date.and_hms_opt(12, 0, 0))
+        .unwrap();
+
+    let anal = AnalyzedData {
+        valid_time: init_time,
+        lead_time: 0,
+        hdw: 150.0,
+        ffwi: 20.0,
+        blow_up_dt: CelsiusDiff(4.0),
+        blow_up_height: Meters(2500.0),
+        haines_index: 5,
+        mixing_height: Meters(3000.0),
+        surface_rh: 15.0,
+        surface_temperature_c: 32.0,
+        surface_wind_speed_ms: 8.0,
+        surface_wind_direction_deg: 225.0,
+        transport_wind_speed_ms: 12.0,
+        transport_wind_dir_deg: 240.0,
+    };
+
+    EnsembleSeriesBuilder::new()
+        .site(SiteInfo::default())
+        .model("GFS")
+        .add_run(init_time, TimeSeries { data: vec![anal] })
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn ensemble_series_is_send_and_sync() {
+    assert_send_sync::<EnsembleSeries<AnalyzedData>>();
+
+    let ens = Arc::new(ensemble());
+    let (sender, receiver) = mpsc::channel();
+
+    let shared = Arc::clone(&ens);
+    rayon::spawn(move || {
+        sender.send(shared.total_time_steps()).unwrap();
+    });
+
+    assert_eq!(receiver.recv().unwrap(), ens.total_time_steps());
+}